@@ -0,0 +1,473 @@
+//! The Buoyancy Interchange Transmission System (BITS) packet format from day 16
+
+use std::error::Error;
+use std::fmt;
+
+/// A packet along with the packets it contains
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Packet {
+    pub version: u8,
+    pub body: Body,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Body {
+    Literal(u64),
+    Operator {
+        op: Operator,
+        /// How the sub-packets were delimited, kept so encoding gives back the same bits
+        length: LengthType,
+        packets: Vec<Packet>,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Operator {
+    Sum,
+    Product,
+    Minimum,
+    Maximum,
+    GreaterThan,
+    LessThan,
+    EqualTo,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LengthType {
+    /// Length type ID 0: the total number of bits in the sub-packets
+    Bits,
+    /// Length type ID 1: the number of sub-packets
+    Packets,
+}
+
+impl Operator {
+    fn from_type_id(type_id: u64) -> Option<Self> {
+        match type_id {
+            0 => Some(Operator::Sum),
+            1 => Some(Operator::Product),
+            2 => Some(Operator::Minimum),
+            3 => Some(Operator::Maximum),
+            5 => Some(Operator::GreaterThan),
+            6 => Some(Operator::LessThan),
+            7 => Some(Operator::EqualTo),
+            _ => None,
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Operator::Sum => "sum",
+            Operator::Product => "product",
+            Operator::Minimum => "min",
+            Operator::Maximum => "max",
+            Operator::GreaterThan => "gt",
+            Operator::LessThan => "lt",
+            Operator::EqualTo => "eq",
+        }
+    }
+}
+
+impl Packet {
+    /// Sum of the versions of this packet and every packet inside it
+    pub fn version_sum(&self) -> u64 {
+        let inner = match &self.body {
+            Body::Literal(_) => 0,
+            Body::Operator { packets, .. } => packets.iter().map(|p| p.version_sum()).sum(),
+        };
+        self.version as u64 + inner
+    }
+
+    /// The value of the expression the packet represents
+    pub fn eval(&self) -> u64 {
+        let (op, packets) = match &self.body {
+            Body::Literal(value) => return *value,
+            Body::Operator { op, packets, .. } => (op, packets),
+        };
+        let mut values = packets.iter().map(|p| p.eval());
+        // decoding guarantees min and max have operands, and comparisons exactly two
+        match op {
+            Operator::Sum => values.sum(),
+            Operator::Product => values.product(),
+            Operator::Minimum => values.min().unwrap(),
+            Operator::Maximum => values.max().unwrap(),
+            Operator::GreaterThan => (values.next() > values.next()) as u64,
+            Operator::LessThan => (values.next() < values.next()) as u64,
+            Operator::EqualTo => (values.next() == values.next()) as u64,
+        }
+    }
+
+    fn fmt_tree(&self, f: &mut fmt::Formatter, depth: usize) -> fmt::Result {
+        write!(f, "{:indent$}", "", indent = depth * 2)?;
+        match &self.body {
+            Body::Literal(value) => writeln!(f, "{} (v{})", value, self.version),
+            Body::Operator {
+                op,
+                length,
+                packets,
+            } => {
+                writeln!(f, "{} (v{}, {:?})", op.name(), self.version, length)?;
+                packets.iter().try_for_each(|p| p.fmt_tree(f, depth + 1))
+            }
+        }
+    }
+}
+
+impl fmt::Display for Packet {
+    /// The expression, like `sum(1, product(2, 3))`, or with `{:#}` an indented tree which
+    /// also shows versions and length types
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if f.alternate() {
+            return self.fmt_tree(f, 0);
+        }
+        match &self.body {
+            Body::Literal(value) => write!(f, "{}", value),
+            Body::Operator { op, packets, .. } => {
+                write!(f, "{}(", op.name())?;
+                for (i, packet) in packets.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", packet)?;
+                }
+                write!(f, ")")
+            }
+        }
+    }
+}
+
+/// Why a transmission couldn't be decoded, and the offset of the bit where it happened
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DecodeError {
+    pub bit: usize,
+    pub expected: String,
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "bit {}: expected {}", self.bit, self.expected)
+    }
+}
+
+impl Error for DecodeError {}
+
+/// Reads bits one at a time out of hex digits
+struct BitReader {
+    nibbles: Vec<u8>,
+    position: usize,
+}
+
+impl BitReader {
+    fn new(hex: &str) -> Result<Self, DecodeError> {
+        let nibbles = hex
+            .chars()
+            .enumerate()
+            .map(|(i, c)| {
+                c.to_digit(16).map(|d| d as u8).ok_or_else(|| DecodeError {
+                    bit: i * 4,
+                    expected: "a hex digit".to_string(),
+                })
+            })
+            .collect::<Result<Vec<u8>, DecodeError>>()?;
+        Ok(Self {
+            nibbles,
+            position: 0,
+        })
+    }
+
+    fn error(&self, expected: &str) -> DecodeError {
+        DecodeError {
+            bit: self.position,
+            expected: expected.to_string(),
+        }
+    }
+
+    fn remaining(&self) -> usize {
+        self.nibbles.len() * 4 - self.position
+    }
+
+    /// Reads an `n` bit number, most significant bit first
+    fn read(&mut self, n: usize, expected: &str) -> Result<u64, DecodeError> {
+        if n > self.remaining() {
+            return Err(self.error(expected));
+        }
+        let mut value = 0;
+        for _ in 0..n {
+            let nibble = self.nibbles[self.position / 4];
+            let bit = (nibble >> (3 - self.position % 4)) & 1;
+            value = (value << 1) | bit as u64;
+            self.position += 1;
+        }
+        Ok(value)
+    }
+
+    fn packet(&mut self) -> Result<Packet, DecodeError> {
+        let start = self.position;
+        let version = self.read(3, "a packet version")? as u8;
+        let type_id = self.read(3, "a packet type ID")?;
+        let op = match Operator::from_type_id(type_id) {
+            Some(op) => op,
+            None => {
+                return Ok(Packet {
+                    version,
+                    body: Body::Literal(self.literal()?),
+                })
+            }
+        };
+
+        let mut packets = Vec::new();
+        let length = match self.read(1, "a length type ID")? {
+            0 => {
+                let bits = self.read(15, "a sub-packet length in bits")? as usize;
+                let end = self.position + bits;
+                while self.position < end {
+                    packets.push(self.packet()?);
+                }
+                if self.position != end {
+                    return Err(self.error(&format!("sub-packets totalling {} bits", bits)));
+                }
+                LengthType::Bits
+            }
+            _ => {
+                let count = self.read(11, "a number of sub-packets")?;
+                for _ in 0..count {
+                    packets.push(self.packet()?);
+                }
+                LengthType::Packets
+            }
+        };
+
+        let operands = match op {
+            Operator::GreaterThan | Operator::LessThan | Operator::EqualTo => packets.len() == 2,
+            Operator::Minimum | Operator::Maximum => !packets.is_empty(),
+            Operator::Sum | Operator::Product => true,
+        };
+        if !operands {
+            return Err(DecodeError {
+                bit: start,
+                expected: format!("valid operands for {}", op.name()),
+            });
+        }
+        Ok(Packet {
+            version,
+            body: Body::Operator {
+                op,
+                length,
+                packets,
+            },
+        })
+    }
+
+    fn literal(&mut self) -> Result<u64, DecodeError> {
+        let mut value: u64 = 0;
+        loop {
+            let more = self.read(1, "a literal group")?;
+            if value >> 60 != 0 {
+                return Err(self.error("a literal of at most 64 bits"));
+            }
+            value = (value << 4) | self.read(4, "a literal group")?;
+            if more == 0 {
+                return Ok(value);
+            }
+        }
+    }
+}
+
+/// Decodes a hex transmission holding a single outermost packet, followed only by zeros
+pub fn decode(hex: &str) -> Result<Packet, DecodeError> {
+    let mut reader = BitReader::new(hex)?;
+    let packet = reader.packet()?;
+    while reader.remaining() > 0 {
+        if reader.read(1, "zero padding")? != 0 {
+            reader.position -= 1;
+            return Err(reader.error("zero padding"));
+        }
+    }
+    Ok(packet)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const LITERAL_TYPE_ID: u64 = 4;
+
+    impl Operator {
+        fn type_id(&self) -> u64 {
+            match self {
+                Operator::Sum => 0,
+                Operator::Product => 1,
+                Operator::Minimum => 2,
+                Operator::Maximum => 3,
+                Operator::GreaterThan => 5,
+                Operator::LessThan => 6,
+                Operator::EqualTo => 7,
+            }
+        }
+    }
+
+    /// Collects bits to be written out as hex
+    #[derive(Default)]
+    struct BitWriter {
+        bits: Vec<bool>,
+    }
+
+    impl BitWriter {
+        fn write(&mut self, n: usize, value: u64) {
+            assert!(n == 64 || value >> n == 0, "{} doesn't fit in {} bits", value, n);
+            self.bits.extend((0..n).rev().map(|i| (value >> i) & 1 == 1));
+        }
+
+        fn packet(&mut self, packet: &Packet) {
+            self.write(3, packet.version as u64);
+            match &packet.body {
+                Body::Literal(value) => {
+                    self.write(3, LITERAL_TYPE_ID);
+                    let groups = ((64 - value.leading_zeros() as usize).div_ceil(4)).max(1);
+                    for i in (0..groups).rev() {
+                        self.write(1, (i > 0) as u64);
+                        self.write(4, (value >> (i * 4)) & 0xf);
+                    }
+                }
+                Body::Operator {
+                    op,
+                    length,
+                    packets,
+                } => {
+                    self.write(3, op.type_id());
+                    let mut inner = BitWriter::default();
+                    packets.iter().for_each(|p| inner.packet(p));
+                    match length {
+                        LengthType::Bits => {
+                            self.write(1, 0);
+                            self.write(15, inner.bits.len() as u64);
+                        }
+                        LengthType::Packets => {
+                            self.write(1, 1);
+                            self.write(11, packets.len() as u64);
+                        }
+                    }
+                    self.bits.extend(inner.bits);
+                }
+            }
+        }
+
+        /// The bits as upper case hex, padded with zeros to a whole number of digits
+        fn hex(&self) -> String {
+            self.bits
+                .chunks(4)
+                .map(|chunk| {
+                    let bit = |i: usize| chunk.get(i).copied().unwrap_or(false) as u32;
+                    let nibble = (0..4).fold(0, |n, i| (n << 1) | bit(i));
+                    char::from_digit(nibble, 16).unwrap().to_ascii_uppercase()
+                })
+                .collect()
+        }
+    }
+
+    /// Encodes a packet as hex, using the fewest literal groups. Panics if a version, sub-packet
+    /// length or count doesn't fit in its field.
+    fn encode(packet: &Packet) -> String {
+        let mut writer = BitWriter::default();
+        writer.packet(packet);
+        writer.hex()
+    }
+
+    fn literal(version: u8, value: u64) -> Packet {
+        Packet {
+            version,
+            body: Body::Literal(value),
+        }
+    }
+
+    #[test]
+    fn test_decode() {
+        assert_eq!(decode("D2FE28"), Ok(literal(6, 2021)));
+        assert_eq!(
+            decode("38006F45291200"),
+            Ok(Packet {
+                version: 1,
+                body: Body::Operator {
+                    op: Operator::LessThan,
+                    length: LengthType::Bits,
+                    packets: vec![literal(6, 10), literal(2, 20)],
+                },
+            })
+        );
+        let packet = decode("EE00D40C823060").unwrap();
+        assert_eq!(packet.to_string(), "max(1, 2, 3)");
+        assert_eq!(
+            format!("{:#}", packet),
+            "max (v7, Packets)\n  1 (v2)\n  2 (v4)\n  3 (v1)\n"
+        );
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        assert_eq!(
+            decode("D2FG28"),
+            Err(DecodeError {
+                bit: 12,
+                expected: "a hex digit".to_string()
+            })
+        );
+        assert_eq!(decode("D2F").unwrap_err().expected, "a literal group");
+        assert_eq!(decode("D2FE2801").unwrap_err().bit, 31);
+        // a less-than packet holding three literals
+        assert_eq!(decode("FA00D40C823060").unwrap_err().bit, 0);
+    }
+
+    #[test]
+    fn test_version_sum() {
+        let cases = [
+            ("8A004A801A8002F478", 16),
+            ("620080001611562C8802118E34", 12),
+            ("C0015000016115A2E0802F182340", 23),
+            ("A0016C880162017C3686B18A3D4780", 31),
+        ];
+        for (hex, sum) in cases {
+            assert_eq!(decode(hex).unwrap().version_sum(), sum, "{}", hex);
+        }
+    }
+
+    #[test]
+    fn test_eval() {
+        let cases = [
+            ("C200B40A82", 3),
+            ("04005AC33890", 54),
+            ("880086C3E88112", 7),
+            ("CE00C43D881120", 9),
+            ("D8005AC2A8F0", 1),
+            ("F600BC2D8F", 0),
+            ("9C005AC2F8F0", 0),
+            ("9C0141080250320F1802104A08", 1),
+        ];
+        for (hex, value) in cases {
+            assert_eq!(decode(hex).unwrap().eval(), value, "{}", hex);
+        }
+        assert_eq!(
+            decode("9C0141080250320F1802104A08").unwrap().to_string(),
+            "eq(sum(1, 3), product(2, 2))"
+        );
+    }
+
+    #[test]
+    fn test_encode_round_trip() {
+        let hexes = ["D2FE28", "38006F45291200", "EE00D40C823060", "9C0141080250320F1802104A08"];
+        for hex in hexes {
+            let packet = decode(hex).unwrap();
+            let encoded = encode(&packet);
+            assert_eq!(decode(&encoded), Ok(packet));
+            assert!(hex.starts_with(encoded.trim_end_matches('0')), "{} != {}", encoded, hex);
+        }
+
+        let packet = Packet {
+            version: 5,
+            body: Body::Operator {
+                op: Operator::Product,
+                length: LengthType::Bits,
+                packets: vec![literal(0, 0), literal(7, u64::MAX)],
+            },
+        };
+        assert_eq!(decode(&encode(&packet)), Ok(packet));
+    }
+}
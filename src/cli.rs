@@ -0,0 +1,149 @@
+use crate::input::Source;
+use crate::registry::{self, Part, Parts};
+
+pub const USAGE: &str = "\
+Usage: aoc2021 [run] [OPTIONS]
+
+Options:
+    --day <N>        Run a single day (may be repeated)
+    --days <LIST>    Run a list of days and ranges, e.g. 1-9 or 1-3,7,12
+    --all            Run every day (the default)
+    --part <1|2>     Only run the given part of each selected day
+    --input <PATH>   Read the puzzle input from PATH, or stdin if PATH is -
+                     (defaults to inputs/dayNN.txt, then the embedded input)
+    -h, --help       Print this message";
+
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    Run {
+        days: Vec<u32>,
+        parts: Parts,
+        input: Source,
+    },
+    Help,
+}
+
+/// Parses the command line arguments (without the program name)
+pub fn parse_args<I>(args: I) -> Result<Command, String>
+where
+    I: IntoIterator<Item = String>,
+{
+    let mut args = args.into_iter().peekable();
+    if args.peek().map(|a| a.as_str()) == Some("run") {
+        args.next();
+    }
+
+    let mut days = Vec::new();
+    let mut all = false;
+    let mut parts = Parts::all();
+    let mut input = Source::Default;
+    while let Some(arg) = args.next() {
+        let mut value = |flag: &str| {
+            args.next()
+                .ok_or_else(|| format!("missing value for {}", flag))
+        };
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(Command::Help),
+            "--all" => all = true,
+            "--day" => days.push(parse_day(&value("--day")?)?),
+            "--days" => days.extend(parse_days(&value("--days")?)?),
+            "--part" => {
+                parts = match value("--part")?.as_str() {
+                    "1" => Parts::only(Part::One),
+                    "2" => Parts::only(Part::Two),
+                    other => return Err(format!("invalid part '{}', expected 1 or 2", other)),
+                }
+            }
+            "--input" => input = Source::from_arg(&value("--input")?),
+            other => return Err(format!("unrecognized argument '{}'", other)),
+        }
+    }
+
+    if all || days.is_empty() {
+        days = registry::all().iter().map(|d| d.day).collect();
+    }
+    days.sort_unstable();
+    days.dedup();
+    if let Some(missing) = days.iter().find(|d| registry::find(**d).is_none()) {
+        return Err(format!("day {} is not implemented", missing));
+    }
+    if input != Source::Default && days.len() != 1 {
+        return Err("--input can only be used with a single day".to_string());
+    }
+
+    Ok(Command::Run { days, parts, input })
+}
+
+fn parse_day(s: &str) -> Result<u32, String> {
+    s.trim()
+        .parse::<u32>()
+        .map_err(|_| format!("invalid day '{}'", s))
+}
+
+/// Parses a comma separated list of days and inclusive ranges like "1-3,7"
+fn parse_days(s: &str) -> Result<Vec<u32>, String> {
+    let mut days = Vec::new();
+    for spec in s.split(',') {
+        match spec.split_once('-') {
+            Some((from, to)) => {
+                let (from, to) = (parse_day(from)?, parse_day(to)?);
+                if from > to {
+                    return Err(format!("invalid day range '{}'", spec));
+                }
+                days.extend(from..=to);
+            }
+            None => days.push(parse_day(spec)?),
+        }
+    }
+    Ok(days)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn parse(s: &str) -> Result<Command, String> {
+        parse_args(s.split_whitespace().map(|s| s.to_string()))
+    }
+
+    #[test]
+    fn test_parse_args() {
+        assert_eq!(
+            parse("run --day 12 --part 2"),
+            Ok(Command::Run {
+                days: vec![12],
+                parts: Parts::only(Part::Two),
+                input: Source::Default,
+            })
+        );
+        assert_eq!(
+            parse("--days 1-3,7"),
+            Ok(Command::Run {
+                days: vec![1, 2, 3, 7],
+                parts: Parts::all(),
+                input: Source::Default,
+            })
+        );
+        assert_eq!(
+            parse("--day 3 --input -"),
+            Ok(Command::Run {
+                days: vec![3],
+                parts: Parts::all(),
+                input: Source::Stdin,
+            })
+        );
+        assert_eq!(parse("run --all"), parse(""));
+        assert_eq!(parse("--help"), Ok(Command::Help));
+    }
+
+    #[test]
+    fn test_parse_args_errors() {
+        assert!(parse("--day").is_err());
+        assert!(parse("--day x").is_err());
+        assert!(parse("--days 9-1").is_err());
+        assert!(parse("--part 3").is_err());
+        assert!(parse("--day 99").is_err());
+        assert!(parse("--bogus").is_err());
+        assert!(parse("--days 1-2 --input day01.txt").is_err());
+    }
+}
@@ -1,15 +1,30 @@
-use crate::parsing;
+use crate::parsing::{self, ParseError};
+use crate::solution::Solution;
 
-pub fn run() {
-    let depths = get_depths();
-    println!("Day  1: Depth Increases={}", find_increases(&depths));
-    println!(
-        "      : Depth Sliding Window Increases={}",
-        find_sliding_window_increases(&depths, 3)
-    );
+pub struct Day01 {
+    depths: Vec<u32>,
 }
 
-pub fn find_increases(d: &Vec<u32>) -> usize {
+impl Solution for Day01 {
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Self, ParseError> {
+        Ok(Self {
+            depths: parse_u32s(input)?,
+        })
+    }
+
+    fn part1(&self) -> usize {
+        find_increases(&self.depths)
+    }
+
+    fn part2(&self) -> usize {
+        find_sliding_window_increases(&self.depths, 3)
+    }
+}
+
+pub fn find_increases(d: &[u32]) -> usize {
     d.iter()
         .enumerate()
         .skip(1)
@@ -17,7 +32,7 @@ pub fn find_increases(d: &Vec<u32>) -> usize {
         .count()
 }
 
-pub fn find_sliding_window_increases(d: &Vec<u32>, window: u32) -> usize {
+pub fn find_sliding_window_increases(d: &[u32], window: u32) -> usize {
     d.iter()
         .enumerate()
         .skip(window as usize)
@@ -28,57 +43,14 @@ pub fn find_sliding_window_increases(d: &Vec<u32>, window: u32) -> usize {
         .count()
 }
 
-fn parse_u32s(s: &str) -> Vec<u32> {
-    parsing::parse_input(s, |parts| parts[0].parse::<u32>().unwrap())
+fn parse_u32s(s: &str) -> Result<Vec<u32>, ParseError> {
+    parsing::parse_input(s, |line| {
+        line.end(1)?;
+        line.parse(0, "a depth")
+    })
 }
 
-#[cfg(test)]
-mod test {
-    use super::*;
-
-    #[test]
-    fn test_find_increases() {
-        let depths = parse_u32s(
-            r"
-            199
-            200
-            208
-            210
-            200
-            207
-            240
-            269
-            260
-            263
-        ",
-        );
-        assert_eq!(find_increases(&depths), 7);
-    }
-
-    #[test]
-    fn test_find_sliding_window_increases() {
-        let depths = parse_u32s(
-            r"
-            199
-            200
-            208
-            210
-            200
-            207
-            240
-            269
-            260
-            263
-        ",
-        );
-        assert_eq!(find_sliding_window_increases(&depths, 1), 7);
-        assert_eq!(find_sliding_window_increases(&depths, 3), 5);
-    }
-}
-
-pub fn get_depths() -> Vec<u32> {
-    parse_u32s(
-        r"
+pub const INPUT: &str = r"
 118
 121
 123
@@ -2079,6 +2051,54 @@ pub fn get_depths() -> Vec<u32> {
 7951
 7967
 7963
-",
-    )
+";
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_find_increases() {
+        let depths = parse_u32s(
+            r"
+            199
+            200
+            208
+            210
+            200
+            207
+            240
+            269
+            260
+            263
+        ",
+        ).unwrap();
+        assert_eq!(find_increases(&depths), 7);
+    }
+
+    #[test]
+    fn test_find_sliding_window_increases() {
+        let depths = parse_u32s(
+            r"
+            199
+            200
+            208
+            210
+            200
+            207
+            240
+            269
+            260
+            263
+        ",
+        ).unwrap();
+        assert_eq!(find_sliding_window_increases(&depths, 1), 7);
+        assert_eq!(find_sliding_window_increases(&depths, 3), 5);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let err = parse_u32s("199\n199 garbage").unwrap_err();
+        assert_eq!((err.line, err.column, err.token.as_str()), (2, 5, "garbage"));
+    }
 }
@@ -1,61 +1,116 @@
-use crate::parsing;
+use crate::parsing::{self, ParseError};
+use crate::solution::Solution;
 
-pub fn run() {
-    let cmds = get_cmds();
-    let (distance, depth) = pilot(&cmds);
-    println!(
-        "Day  2: Piloted forward {} and at a depth of {} for a total of {}",
-        distance,
-        depth,
-        distance * depth
-    );
+pub struct Day02 {
+    cmds: Vec<Command>,
 }
 
-/// Returns (distance, depth) after piloting the moves
-pub fn pilot(cmds: &Vec<(String, u32)>) -> (u32, u32) {
-    let mut depth = 0;
-    let mut distance = 0;
-    cmds.iter().for_each(|(dir, dist)| match dir.as_str() {
-        "forward" => distance += dist,
-        "down" => depth += dist,
-        "up" => depth -= dist,
-        _ => {}
-    });
+impl Solution for Day02 {
+    type Answer1 = i64;
+    type Answer2 = i64;
 
-    (distance, depth)
+    fn parse(input: &str) -> Result<Self, ParseError> {
+        Ok(Self {
+            cmds: parse_cmds(input)?,
+        })
+    }
+
+    fn part1(&self) -> i64 {
+        let pos = pilot(&self.cmds, Model::Simple).position();
+        pos.distance * pos.depth
+    }
+
+    fn part2(&self) -> i64 {
+        let pos = pilot(&self.cmds, Model::Aim).position();
+        pos.distance * pos.depth
+    }
 }
 
-fn parse_cmds(s: &str) -> Vec<(String, u32)> {
-    parsing::parse_input(s, |parts| {
-        (parts[0].to_string(), parts[1].parse::<u32>().unwrap())
-    })
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Command {
+    Forward(u32),
+    Down(u32),
+    Up(u32),
 }
 
-#[cfg(test)]
-mod test {
-    use super::*;
+/// How the submarine interprets its commands
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Model {
+    /// down/up change the depth directly
+    Simple,
+    /// down/up change the aim, and forward dives by aim * distance
+    Aim,
+}
 
-    #[test]
-    fn test_pilot() {
-        let cmds = parse_cmds(
-            r"
-            forward 5
-            down 5
-            forward 8
-            up 3
-            down 8
-            forward 2
-        ",
-        );
-        let (distance, depth) = pilot(&cmds);
-        assert_eq!(distance, 15);
-        assert_eq!(depth, 10);
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Position {
+    pub distance: i64,
+    pub depth: i64,
+    pub aim: i64,
+}
+
+pub struct Submarine {
+    model: Model,
+    trajectory: Vec<Position>,
+}
+
+impl Submarine {
+    pub fn new(model: Model) -> Self {
+        Self {
+            model,
+            trajectory: vec![Position::default()],
+        }
+    }
+
+    pub fn execute(&mut self, cmd: &Command) {
+        let mut pos = self.position();
+        match (self.model, *cmd) {
+            (Model::Simple, Command::Forward(x)) => pos.distance += x as i64,
+            (Model::Simple, Command::Down(x)) => pos.depth += x as i64,
+            (Model::Simple, Command::Up(x)) => pos.depth -= x as i64,
+            (Model::Aim, Command::Forward(x)) => {
+                pos.distance += x as i64;
+                pos.depth += pos.aim * x as i64;
+            }
+            (Model::Aim, Command::Down(x)) => pos.aim += x as i64,
+            (Model::Aim, Command::Up(x)) => pos.aim -= x as i64,
+        }
+        self.trajectory.push(pos);
+    }
+
+    pub fn position(&self) -> Position {
+        *self.trajectory.last().unwrap()
+    }
+
+    /// Every position the submarine has been in, starting from the surface
+    #[cfg(test)]
+    pub fn trajectory(&self) -> &[Position] {
+        &self.trajectory
     }
 }
 
-fn get_cmds() -> Vec<(String, u32)> {
-    parse_cmds(
-        r"
+/// Returns the submarine after piloting the moves
+pub fn pilot(cmds: &[Command], model: Model) -> Submarine {
+    let mut sub = Submarine::new(model);
+    cmds.iter().for_each(|cmd| sub.execute(cmd));
+    sub
+}
+
+fn parse_cmds(s: &str) -> Result<Vec<Command>, ParseError> {
+    parsing::parse_input(s, |line| {
+        let dir = line.token(0, "a direction")?;
+        let dist = line.parse(1, "a distance")?;
+        line.end(2)?;
+        match dir.text {
+            "forward" => Ok(Command::Forward(dist)),
+            "down" => Ok(Command::Down(dist)),
+            "up" => Ok(Command::Up(dist)),
+            _ => Err(dir.error("one of forward, down or up")),
+        }
+    })
+}
+
+pub const INPUT: &str = r"
 forward 5
 forward 2
 forward 9
@@ -1056,6 +1111,57 @@ forward 8
 forward 5
 down 9
 forward 6
-               ",
-    )
+               ";
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const CMDS: &str = r"
+        forward 5
+        down 5
+        forward 8
+        up 3
+        down 8
+        forward 2
+    ";
+
+    #[test]
+    fn test_pilot() {
+        let cmds = parse_cmds(CMDS).unwrap();
+        let pos = pilot(&cmds, Model::Simple).position();
+        assert_eq!(pos.distance, 15);
+        assert_eq!(pos.depth, 10);
+    }
+
+    #[test]
+    fn test_pilot_with_aim() {
+        let cmds = parse_cmds(CMDS).unwrap();
+        let sub = pilot(&cmds, Model::Aim);
+        assert_eq!(sub.position().distance, 15);
+        assert_eq!(sub.position().depth, 60);
+        assert_eq!(
+            sub.trajectory()
+                .iter()
+                .map(|p| (p.distance, p.depth, p.aim))
+                .collect::<Vec<_>>(),
+            vec![
+                (0, 0, 0),
+                (5, 0, 0),
+                (5, 0, 5),
+                (13, 40, 5),
+                (13, 40, 2),
+                (13, 40, 10),
+                (15, 60, 10),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_cmds_errors() {
+        let err = parse_cmds("forward 5\nsideways 2").unwrap_err();
+        assert_eq!((err.line, err.column, err.token.as_str()), (2, 1, "sideways"));
+        let err = parse_cmds("forward 5 extra").unwrap_err();
+        assert_eq!((err.column, err.token.as_str()), (11, "extra"));
+    }
 }
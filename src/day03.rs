@@ -1,74 +1,114 @@
-use crate::parsing;
+use crate::parsing::{self, ParseError};
+use crate::solution::Solution;
 
-pub fn run() {
-    let diagnostics = get_diagnostics();
-    let (gamma, epsilon) = find_bit_frequencies(&diagnostics);
-    println!("Day  3: Gamma={}, Epsilon={}, Power Consumption={}", gamma, epsilon, gamma * &epsilon);
+pub struct Day03 {
+    diagnostics: Diagnostics,
+}
+
+impl Solution for Day03 {
+    type Answer1 = u64;
+    type Answer2 = u64;
+
+    fn parse(input: &str) -> Result<Self, ParseError> {
+        Ok(Self {
+            diagnostics: parse_diagnostics(input)?,
+        })
+    }
+
+    fn part1(&self) -> u64 {
+        let (gamma, epsilon) = find_bit_frequencies(&self.diagnostics);
+        gamma * epsilon
+    }
+
+    fn part2(&self) -> u64 {
+        let (oxygen, co2) = find_life_support(&self.diagnostics).expect("no diagnostics");
+        oxygen * co2
+    }
+}
+
+/// Diagnostic report numbers, all written with the same number of bits
+#[derive(Debug)]
+pub struct Diagnostics {
+    pub width: usize,
+    pub values: Vec<u64>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BitCriteria {
+    /// keep numbers with the most common bit, 1 on ties (oxygen generator)
+    MostCommon,
+    /// keep numbers with the least common bit, 0 on ties (CO2 scrubber)
+    LeastCommon,
+}
+
+fn count_ones(values: &[u64], bit: usize) -> usize {
+    values.iter().filter(|v| *v & (1 << bit) != 0).count()
 }
 
 /// Returns (gamma, epsilon) aka (most common bits, least common bits)
-pub fn find_bit_frequencies(d: &Vec<u64>) -> (u64, u64) {
-    #[derive(Debug)]
-    struct Count(u32, u32);
-    let mut counts = Vec::<Count>::new();
-    d.iter().for_each(|num| {
-        let mut mask = 1;
-        let mut pos = 0;
-        while &mask < num || pos < counts.len() {
-            if counts.len() <= pos { counts.push(Count(0, 0)); }
-            counts.get_mut(pos)
-                .and_then(|count| {
-                    if mask & num > 0 { (*count).1 += 1; } else { (*count).0 += 1; };
-                    Some(count)
-                });
-            pos += 1;
-            mask *= 2;
-        }
-    });
+pub fn find_bit_frequencies(d: &Diagnostics) -> (u64, u64) {
     let mut gamma = 0;
     let mut epsilon = 0;
-    let mut order = 1;
-    counts.iter().for_each(|count| {
-        gamma += order * (if count.1 > count.0 { 1 } else { 0 });
-        epsilon += order * (if count.1 < count.0 { 1 } else { 0 });
-        order *= 2;
+    (0..d.width).for_each(|bit| {
+        let ones = count_ones(&d.values, bit);
+        let zeros = d.values.len() - ones;
+        if ones > zeros {
+            gamma |= 1 << bit;
+        } else if ones < zeros {
+            epsilon |= 1 << bit;
+        }
     });
     (gamma, epsilon)
 }
 
-fn parse_binary_u64s(s: &str) -> Vec<u64> {
-    parsing::parse_input(s, |parts| isize::from_str_radix(parts[0], 2).unwrap() as u64)
+/// Filters the numbers bit by bit, from the most significant, until one remains
+pub fn find_rating(d: &Diagnostics, criteria: BitCriteria) -> Option<u64> {
+    let mut remaining = d.values.clone();
+    for bit in (0..d.width).rev() {
+        if remaining.len() <= 1 {
+            break;
+        }
+        let ones = count_ones(&remaining, bit);
+        let zeros = remaining.len() - ones;
+        if ones == 0 || zeros == 0 {
+            // every number agrees on this bit, so it can't tell them apart
+            continue;
+        }
+        let keep_ones = match criteria {
+            BitCriteria::MostCommon => ones >= zeros,
+            BitCriteria::LeastCommon => ones < zeros,
+        };
+        remaining.retain(|v| (v & (1 << bit) != 0) == keep_ones);
+    }
+    remaining.first().copied()
 }
 
-#[cfg(test)]
-mod test {
-    use super::*;
+/// Returns (oxygen generator rating, CO2 scrubber rating)
+pub fn find_life_support(d: &Diagnostics) -> Option<(u64, u64)> {
+    Some((
+        find_rating(d, BitCriteria::MostCommon)?,
+        find_rating(d, BitCriteria::LeastCommon)?,
+    ))
+}
 
-    #[test]
-    fn test_find_bit_frequencies() {
-        let numbers = parse_binary_u64s(r"
-            00100
-            11110
-            10110
-            10111
-            10101
-            01111
-            00111
-            11100
-            10000
-            11001
-            00010
-            01010
-        ");
-        let (gamma, epsilon) = find_bit_frequencies(&numbers);
-        assert_eq!(gamma, 22);
-        assert_eq!(epsilon, 9);
-        assert_eq!(gamma * epsilon, 198);
+fn parse_diagnostics(s: &str) -> Result<Diagnostics, ParseError> {
+    let mut width = None;
+    let values = parsing::parse_input(s, |line| {
+        let token = line.token(0, "a binary number")?;
+        line.end(1)?;
+        let expected_width = *width.get_or_insert(token.text.len());
+        if token.text.len() != expected_width || expected_width > 64 {
+            return Err(token.error(&format!("a binary number of {} bits", expected_width.min(64))));
+        }
+        u64::from_str_radix(token.text, 2).map_err(|_| token.error("a binary number"))
+    })?;
+    match width {
+        Some(width) => Ok(Diagnostics { width, values }),
+        None => Err(parsing::end_of_input(s, "a binary number")),
     }
 }
 
-fn get_diagnostics() -> Vec<u64> {
-    parse_binary_u64s(r"
+pub const INPUT: &str = r"
 010100110111
 101001010000
 101011110010
@@ -1069,5 +1109,85 @@ fn get_diagnostics() -> Vec<u64> {
 010010011101
 100011110000
 110011110111
-    ")
-}
\ No newline at end of file
+    ";
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXAMPLE: &str = r"
+        00100
+        11110
+        10110
+        10111
+        10101
+        01111
+        00111
+        11100
+        10000
+        11001
+        00010
+        01010
+    ";
+
+    #[test]
+    fn test_find_bit_frequencies() {
+        let diagnostics = parse_diagnostics(EXAMPLE).unwrap();
+        assert_eq!(diagnostics.width, 5);
+        let (gamma, epsilon) = find_bit_frequencies(&diagnostics);
+        assert_eq!(gamma, 22);
+        assert_eq!(epsilon, 9);
+        assert_eq!(gamma * epsilon, 198);
+    }
+
+    #[test]
+    fn test_find_bit_frequencies_leading_zeros() {
+        let diagnostics = parse_diagnostics("0001\n0011").unwrap();
+        assert_eq!(diagnostics.width, 4);
+        assert_eq!(find_bit_frequencies(&diagnostics), (0b0001, 0b1100));
+    }
+
+    #[test]
+    fn test_find_life_support() {
+        let diagnostics = parse_diagnostics(EXAMPLE).unwrap();
+        assert_eq!(find_rating(&diagnostics, BitCriteria::MostCommon), Some(23));
+        assert_eq!(find_rating(&diagnostics, BitCriteria::LeastCommon), Some(10));
+        assert_eq!(find_life_support(&diagnostics), Some((23, 10)));
+    }
+
+    #[test]
+    fn test_find_life_support_ties() {
+        let diagnostics = parse_diagnostics("10\n01").unwrap();
+        assert_eq!(find_life_support(&diagnostics), Some((0b10, 0b01)));
+
+        let diagnostics = parse_diagnostics("0110\n0101\n1100\n1011").unwrap();
+        assert_eq!(find_life_support(&diagnostics), Some((0b1100, 0b0101)));
+    }
+
+    #[test]
+    fn test_find_life_support_widths() {
+        let diagnostics = parse_diagnostics("1").unwrap();
+        assert_eq!(find_life_support(&diagnostics), Some((1, 1)));
+
+        let diagnostics = parse_diagnostics("000000000001\n000000000000\n100000000000").unwrap();
+        assert_eq!(diagnostics.width, 12);
+        assert_eq!(find_life_support(&diagnostics), Some((0b000000000001, 0b100000000000)));
+
+        let empty = Diagnostics {
+            width: 0,
+            values: Vec::new(),
+        };
+        assert!(find_life_support(&empty).is_none());
+        assert!(parse_diagnostics("").is_err());
+    }
+
+    #[test]
+    fn test_parse_diagnostics_errors() {
+        let err = parse_diagnostics("0101\n011\n").unwrap_err();
+        assert_eq!((err.line, err.expected.as_str()), (2, "a binary number of 4 bits"));
+        let err = parse_diagnostics("0121").unwrap_err();
+        assert_eq!(err.expected, "a binary number");
+        let err = parse_diagnostics("  \n").unwrap_err();
+        assert_eq!((err.line, err.column, err.expected.as_str()), (1, 1, "a binary number"));
+    }
+}
@@ -1,135 +1,199 @@
-use crate::parsing;
-
-pub fn run() {
-    let bingo_data = get_bingo();
-    match play_bingo(&bingo_data.0, &bingo_data.1) {
-        Some(results) => println!(
-            "Day  4: Board {} wins bingo with a score of {}",
-            results.0, results.1
-        ),
-        None => println!("Day  4: No one wins bingo!"),
+use std::fmt;
+
+use crate::parsing::{self, ParseError};
+use crate::solution::Solution;
+
+pub struct Day04 {
+    game: BingoGame,
+}
+
+impl Solution for Day04 {
+    type Answer1 = Score;
+    type Answer2 = Score;
+
+    fn parse(input: &str) -> Result<Self, ParseError> {
+        let (calls, boards) = parse_bingo(input)?;
+        Ok(Self {
+            game: BingoGame::play(&calls, boards),
+        })
+    }
+
+    fn part1(&self) -> Score {
+        Score(self.game.first_winner().map(|win| win.score))
+    }
+
+    fn part2(&self) -> Score {
+        Score(self.game.last_winner().map(|win| win.score))
     }
 }
 
-/// Returns index of winning board and score
-type Board = [u32; 25];
-pub fn play_bingo(calls: &Vec<u32>, boards: &Vec<Board>) -> Option<(usize, u64)> {
-    let mut scores = Vec::<Board>::with_capacity(boards.len());
-    scores.resize(boards.len(), [0; 25]);
-    let board_wins = |b: &Board| -> bool {
-        for row in 0..5 {
-            let r = 5 * row;
-            if b[r] == 1 && b[r + 1] == 1 && b[r + 2] == 1 && b[r + 3] == 1 && b[r + 4] == 1 {
-                return true;
-            }
-        }
-        for c in 0..5 {
-            if b[c] == 1 && b[c + 5] == 1 && b[c + 10] == 1 && b[c + 15] == 1 && b[c + 20] == 1 {
-                return true;
-            }
-        }
+/// The score of a winning board, if any board wins
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Score(pub Option<u64>);
 
-        false
-    };
-    let board_score = |board: &Board, score: &Board| -> u32 {
-        board
-            .iter()
-            .zip(score.iter())
-            .filter_map(|(b, s)| if *s == 0 { Some(*b) } else { None })
-            .sum()
-    };
-    for call in calls.iter() {
-        for score in scores.iter_mut().enumerate() {
-            for pos in 0..25 {
-                if call == &boards[score.0][pos] {
-                    (score.1)[pos] = 1;
-                }
-            }
-            if board_wins(score.1) {
-                return Some((
-                    score.0,
-                    (call * board_score(&boards[score.0], &score.1)).into(),
-                ));
-            }
+impl fmt::Display for Score {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.0 {
+            Some(score) => write!(f, "{}", score),
+            None => write!(f, "no winner"),
         }
     }
-    return None;
 }
 
-fn parse_bingo(s: &str) -> (Vec<u32>, Vec<[u32; 25]>) {
-    let mut calls = Vec::<u32>::new();
-    let mut boards = Vec::<[u32; 25]>::new();
-
-    let mut row: i32 = -1;
-    parsing::parse_input(s, |parts| {
-        if row < 0 {
-            parts[0]
-                .split(",")
-                .map(|x| x.parse::<u32>().unwrap())
-                .for_each(|x| calls.push(x));
-        } else {
-            if row == 0 {
-                let new_board: [u32; 25] = [0 as u32; 25];
-                boards.push(new_board);
+/// A square bingo board of `size` x `size` numbers, stored row by row
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Board {
+    size: usize,
+    numbers: Vec<u32>,
+}
+
+impl Board {
+    pub fn new(size: usize, numbers: Vec<u32>) -> Self {
+        assert_eq!(numbers.len(), size * size, "board must be square");
+        Self { size, numbers }
+    }
+}
+
+/// The row or column which completed a board
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Line {
+    Row(usize),
+    Column(usize),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Win {
+    pub board: usize,
+    /// index into the calls of the number which completed the board
+    pub turn: usize,
+    pub call: u32,
+    pub score: u64,
+    pub line: Line,
+}
+
+/// A game of bingo played through every call, recording when each board won
+pub struct BingoGame {
+    wins: Vec<Option<Win>>,
+}
+
+impl BingoGame {
+    pub fn play(calls: &[u32], boards: Vec<Board>) -> Self {
+        let wins = boards
+            .iter()
+            .enumerate()
+            .map(|(index, board)| Self::play_board(calls, index, board))
+            .collect();
+        Self { wins }
+    }
+
+    fn play_board(calls: &[u32], index: usize, board: &Board) -> Option<Win> {
+        let mut marked = vec![false; board.numbers.len()];
+        let mut row_counts = vec![0; board.size];
+        let mut col_counts = vec![0; board.size];
+        for (turn, call) in calls.iter().enumerate() {
+            let mut line = None;
+            for (pos, number) in board.numbers.iter().enumerate() {
+                if number != call || marked[pos] {
+                    continue;
+                }
+                marked[pos] = true;
+                let (row, col) = (pos / board.size, pos % board.size);
+                row_counts[row] += 1;
+                col_counts[col] += 1;
+                if row_counts[row] == board.size {
+                    line = line.or(Some(Line::Row(row)));
+                } else if col_counts[col] == board.size {
+                    line = line.or(Some(Line::Column(col)));
+                }
             }
-            let board = boards.last_mut().unwrap();
-            let numbers = parts
-                .iter()
-                .map(|x| x.parse::<u32>().unwrap())
-                .collect::<Vec<u32>>();
-            let r = row * 5;
-            for c in 0..5 {
-                board[(r + c) as usize] = numbers[c as usize];
+            if let Some(line) = line {
+                let unmarked = board
+                    .numbers
+                    .iter()
+                    .zip(marked.iter())
+                    .filter(|(_, m)| !**m)
+                    .map(|(n, _)| *n as u64)
+                    .sum::<u64>();
+                return Some(Win {
+                    board: index,
+                    turn,
+                    call: *call,
+                    score: unmarked * *call as u64,
+                    line,
+                });
             }
         }
-        row = (row + 1) % 5;
-    });
-
-    (calls, boards)
-}
+        None
+    }
 
-#[cfg(test)]
-mod test {
-    use super::*;
+    /// Every win in the order they happened, boards winning on the same call by board order
+    pub fn ranking(&self) -> Vec<&Win> {
+        let mut ranking = self.wins.iter().flatten().collect::<Vec<&Win>>();
+        ranking.sort_by_key(|win| (win.turn, win.board));
+        ranking
+    }
 
-    #[test]
-    fn test_play_bingo() {
-        let (calls, boards) = parse_bingo(
-            r"
-            7,4,9,5,11,17,23,2,0,14,21,24,10,16,13,6,15,25,12,22,18,20,8,19,3,26,1
+    pub fn first_winner(&self) -> Option<&Win> {
+        self.ranking().first().copied()
+    }
 
-            22 13 17 11  0
-            8  2 23  4 24
-            21  9 14 16  7
-            6 10  3 18  5
-            1 12 20 15 19
+    pub fn last_winner(&self) -> Option<&Win> {
+        self.ranking().last().copied()
+    }
 
-            3 15  0  2 22
-            9 18 13 17  5
-            19  8  7 25 23
-            20 11 10 24  4
-            14 21 16 12  6
+    /// How a board won, or None if it never wins with these calls
+    #[cfg(test)]
+    pub fn result(&self, board: usize) -> Option<&Win> {
+        self.wins.get(board).and_then(|win| win.as_ref())
+    }
+}
 
-            14 21 17 24  4
-            10 16 15  9 19
-            18  8 23 26 20
-            22 11 13  6  5
-            2  0 12  3  7
-        ",
-        );
-        match play_bingo(&calls, &boards) {
-            Some(result) => {
-                assert_eq!(result.0, 2);
-                assert_eq!(result.1, 4512);
+fn parse_bingo(s: &str) -> Result<(Vec<u32>, Vec<Board>), ParseError> {
+    let mut calls = Vec::<u32>::new();
+    let mut boards = Vec::<Board>::new();
+
+    let mut size = 0;
+    let mut numbers = Vec::new();
+    let mut incomplete = None;
+    parsing::parse_input(s, |line| {
+        if calls.is_empty() {
+            for call in line.token(0, "comma separated calls")?.split(",") {
+                calls.push(call.parse("a called number")?);
             }
-            None => panic!("No winner found!"),
+            return line.end(1);
+        }
+        if numbers.is_empty() {
+            // the first row of a board decides its size
+            size = line.tokens.len();
+        }
+        if line.tokens.len() != size {
+            return Err(line.error(&format!("a board row of {} numbers", size)));
         }
+        for number in line.tokens.iter() {
+            numbers.push(number.parse("a board number")?);
+        }
+        if numbers.len() == size * size {
+            boards.push(Board::new(size, std::mem::take(&mut numbers)));
+            incomplete = None;
+        } else {
+            incomplete = Some(line.error(&format!("a board of {} rows", size)));
+        }
+        Ok(())
+    })?;
+
+    if let Some(err) = incomplete {
+        return Err(err);
     }
+    if calls.is_empty() {
+        return Err(parsing::end_of_input(s, "comma separated calls"));
+    }
+    if boards.is_empty() {
+        return Err(parsing::end_of_input(s, "a bingo board"));
+    }
+    Ok((calls, boards))
 }
 
-fn get_bingo() -> (Vec<u32>, Vec<[u32; 25]>) {
-    parse_bingo(
-        r"
+pub const INPUT: &str = r"
 91,17,64,45,8,13,47,19,52,68,63,76,82,44,28,56,37,2,78,48,32,58,72,53,9,85,77,89,36,22,49,86,51,99,6,92,80,87,7,25,31,66,84,4,98,67,46,61,59,79,0,3,38,27,23,95,20,35,14,30,26,33,42,93,12,57,11,54,50,75,90,41,88,96,40,81,24,94,18,39,70,34,21,55,5,29,71,83,1,60,74,69,10,62,43,73,97,65,15,16
 
 83 40 67 98  4
@@ -731,6 +795,105 @@ fn get_bingo() -> (Vec<u32>, Vec<[u32; 25]>) {
 10 64 92 82  1
 70 12 75 16 14
 68 50 35 73 26
-    ",
-    )
+    ";
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXAMPLE: &str = r"
+            7,4,9,5,11,17,23,2,0,14,21,24,10,16,13,6,15,25,12,22,18,20,8,19,3,26,1
+
+            22 13 17 11  0
+            8  2 23  4 24
+            21  9 14 16  7
+            6 10  3 18  5
+            1 12 20 15 19
+
+            3 15  0  2 22
+            9 18 13 17  5
+            19  8  7 25 23
+            20 11 10 24  4
+            14 21 16 12  6
+
+            14 21 17 24  4
+            10 16 15  9 19
+            18  8 23 26 20
+            22 11 13  6  5
+            2  0 12  3  7
+        ";
+
+    #[test]
+    fn test_play_bingo() {
+        let (calls, boards) = parse_bingo(EXAMPLE).unwrap();
+        let game = BingoGame::play(&calls, boards);
+        match game.first_winner() {
+            Some(result) => {
+                assert_eq!(result.board, 2);
+                assert_eq!(result.score, 4512);
+                assert_eq!(result.call, 24);
+                assert_eq!(result.line, Line::Row(0));
+            }
+            None => panic!("No winner found!"),
+        }
+    }
+
+    #[test]
+    fn test_bingo_last_winner() {
+        let (calls, boards) = parse_bingo(EXAMPLE).unwrap();
+        let game = BingoGame::play(&calls, boards);
+        let last = game.last_winner().unwrap();
+        assert_eq!(last.board, 1);
+        assert_eq!(last.call, 13);
+        assert_eq!(last.score, 1924);
+        assert_eq!(last.line, Line::Column(2));
+        assert_eq!(
+            game.ranking().iter().map(|w| w.board).collect::<Vec<usize>>(),
+            vec![2, 0, 1]
+        );
+    }
+
+    #[test]
+    fn test_bingo_small_boards() {
+        let (calls, boards) = parse_bingo(
+            r"
+            1,4,9,2
+
+            1 2
+            3 4
+
+            5 6
+            7 8
+
+            9 1
+            4 3
+        ",
+        ).unwrap();
+        let game = BingoGame::play(&calls, boards);
+        assert_eq!(game.result(0).map(|w| (w.turn, w.line)), Some((3, Line::Row(0))));
+        assert_eq!(game.result(1), None);
+        assert_eq!(game.result(2).map(|w| (w.turn, w.score)), Some((2, 3 * 9)));
+        assert_eq!(
+            game.ranking().iter().map(|w| w.board).collect::<Vec<usize>>(),
+            vec![2, 0]
+        );
+
+        let day = Day04::parse("1\n\n2 3\n4 5").unwrap();
+        assert_eq!(day.part1().to_string(), "no winner");
+        assert_eq!(day.part2(), Score(None));
+    }
+
+    #[test]
+    fn test_parse_bingo_errors() {
+        let err = parse_bingo("7,4,x\n").unwrap_err();
+        assert_eq!((err.line, err.column, err.token.as_str()), (1, 5, "x"));
+        let err = parse_bingo("7,4\n\n1 2 3 4 5\n6 7 8 9\n").unwrap_err();
+        assert_eq!((err.line, err.expected.as_str()), (4, "a board row of 5 numbers"));
+        let err = parse_bingo("7\n\n1 2\n3 4\n\n5 6\n").unwrap_err();
+        assert_eq!((err.line, err.expected.as_str()), (6, "a board of 2 rows"));
+        let err = parse_bingo("7\n").unwrap_err();
+        assert_eq!((err.line, err.column, err.expected.as_str()), (2, 1, "a bingo board"));
+        let err = parse_bingo("\n\n").unwrap_err();
+        assert_eq!((err.line, err.expected.as_str()), (1, "comma separated calls"));
+    }
 }
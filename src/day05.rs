@@ -1,73 +1,181 @@
-use std::cmp::{max, min};
-use std::collections::HashMap;
+use std::fmt;
 
-use crate::parsing;
+use crate::parsing::{self, ParseError, Token};
+use crate::solution::Solution;
 
-pub fn run() {
-    let (_, num_vent_intersections) = find_intersections(&get_vents());
-    println!("Day  5: Number of vent intersections={}", num_vent_intersections);
+pub struct Day05 {
+    segments: Vec<Segment>,
 }
 
-/// Returns (all represented points, num points with > 1 line through it)
-/// also ignores all angled lines
-pub fn find_intersections(segments : &Vec<((u32,u32),(u32,u32))>) -> (HashMap<(u32,u32), u32>, usize) {
-    let mut intersections = HashMap::new();
-    segments.iter().for_each(|segment| {
-        if segment.0.0 == segment.1.0 {
-            for y in min(segment.0.1, segment.1.1)..=max(segment.0.1, segment.1.1) {
-                *intersections.entry((segment.0.0, y)).or_insert(0) += 1;
-            }
-        } else if segment.0.1 == segment.1.1 {
-            for x in min(segment.0.0, segment.1.0)..=max(segment.0.0, segment.1.0) {
-                *intersections.entry((x, segment.0.1)).or_insert(0) += 1;
-            }
+impl Solution for Day05 {
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Self, ParseError> {
+        Ok(Self {
+            segments: parse_segments(input)?,
+        })
+    }
+
+    fn part1(&self) -> usize {
+        rasterize(&self.segments, SegmentKinds::AXIS_ALIGNED).num_overlaps()
+    }
+
+    fn part2(&self) -> usize {
+        rasterize(&self.segments, SegmentKinds::ALL).num_overlaps()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Orientation {
+    Horizontal,
+    Vertical,
+    /// at exactly 45 degrees
+    Diagonal,
+    /// at any other angle, which can't be rasterized
+    Other,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Segment {
+    pub from: (u32, u32),
+    pub to: (u32, u32),
+}
+
+impl Segment {
+    pub fn orientation(&self) -> Orientation {
+        let dx = self.from.0.abs_diff(self.to.0);
+        let dy = self.from.1.abs_diff(self.to.1);
+        if dy == 0 {
+            Orientation::Horizontal
+        } else if dx == 0 {
+            Orientation::Vertical
+        } else if dx == dy {
+            Orientation::Diagonal
+        } else {
+            Orientation::Other
         }
-    });
+    }
 
-    let num_intersections = intersections.iter()
-        .filter(|x| *x.1 > 1)
-        .count();
+    /// Every point on the segment, from start to end inclusive; segments at other angles have none
+    pub fn points(&self) -> impl Iterator<Item = (u32, u32)> {
+        let step = |from: u32, to: u32| (to as i64 - from as i64).signum();
+        let (dx, dy) = (step(self.from.0, self.to.0), step(self.from.1, self.to.1));
+        let len = match self.orientation() {
+            Orientation::Other => 0,
+            _ => self.from.0.abs_diff(self.to.0).max(self.from.1.abs_diff(self.to.1)) as i64 + 1,
+        };
+        let (x, y) = (self.from.0 as i64, self.from.1 as i64);
+        (0..len).map(move |i| ((x + i * dx) as u32, (y + i * dy) as u32))
+    }
+}
 
-    (intersections, num_intersections)
+/// Which orientations of segments to rasterize
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SegmentKinds {
+    pub horizontal: bool,
+    pub vertical: bool,
+    pub diagonal: bool,
 }
 
-fn parse_segments(s: &str) -> Vec<((u32, u32), (u32, u32))> {
-    parsing::parse_input(s, |parts| {
-        let p1 = parts[0].split(",")
-            .map(|x| x.parse::<u32>().unwrap())
-            .collect::<Vec<u32>>();
-        let p2 = parts[2].split(",")
-            .map(|x| x.parse::<u32>().unwrap())
-            .collect::<Vec<u32>>();
-        ((p1[0], p1[1]), (p2[0], p2[1]))
-    })
+impl SegmentKinds {
+    pub const AXIS_ALIGNED: Self = Self {
+        horizontal: true,
+        vertical: true,
+        diagonal: false,
+    };
+    pub const ALL: Self = Self {
+        horizontal: true,
+        vertical: true,
+        diagonal: true,
+    };
+
+    pub fn includes(&self, segment: &Segment) -> bool {
+        match segment.orientation() {
+            Orientation::Horizontal => self.horizontal,
+            Orientation::Vertical => self.vertical,
+            Orientation::Diagonal => self.diagonal,
+            Orientation::Other => false,
+        }
+    }
 }
 
-#[cfg(test)]
-mod test {
-    use super::*;
+/// Number of segments passing through each point of the ocean floor
+pub struct OverlapMap {
+    width: usize,
+    /// row by row
+    counts: Vec<u32>,
+}
 
-    #[test]
-    fn test_find_intersections() {
-        let segments = parse_segments(r"
-            0,9 -> 5,9
-            8,0 -> 0,8
-            9,4 -> 3,4
-            2,2 -> 2,1
-            7,0 -> 7,4
-            6,4 -> 2,0
-            0,9 -> 2,9
-            3,4 -> 1,4
-            0,0 -> 8,8
-            5,5 -> 8,2
-        ");
-        let (_, num_intersections) = find_intersections(&segments);
-        assert_eq!(num_intersections, 5);
+impl OverlapMap {
+    #[cfg(test)]
+    pub fn count(&self, x: u32, y: u32) -> u32 {
+        let (x, y) = (x as usize, y as usize);
+        match x < self.width {
+            true => self.counts.get(y * self.width + x).copied().unwrap_or(0),
+            false => 0,
+        }
+    }
+
+    /// Number of points with more than one segment through it
+    pub fn num_overlaps(&self) -> usize {
+        self.counts.iter().filter(|c| **c > 1).count()
     }
 }
 
-fn get_vents() -> Vec<((u32, u32), (u32, u32))> {
-    parse_segments(r"
+impl fmt::Display for OverlapMap {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.counts.chunks(self.width.max(1)).try_for_each(|row| {
+            row.iter().try_for_each(|count| match count {
+                0 => write!(f, "."),
+                1..=9 => write!(f, "{}", count),
+                _ => write!(f, "+"),
+            })?;
+            writeln!(f)
+        })
+    }
+}
+
+/// Draws the chosen kinds of segments onto a grid just big enough to cover them
+pub fn rasterize(segments: &[Segment], kinds: SegmentKinds) -> OverlapMap {
+    let included = segments.iter().filter(|segment| kinds.includes(segment));
+    let width = included.clone().map(|s| s.from.0.max(s.to.0) as usize + 1).max().unwrap_or(0);
+    let height = included.clone().map(|s| s.from.1.max(s.to.1) as usize + 1).max().unwrap_or(0);
+    let mut counts = vec![0; width * height];
+    included
+        .flat_map(|segment| segment.points())
+        .for_each(|(x, y)| counts[y as usize * width + x as usize] += 1);
+    OverlapMap { width, counts }
+}
+
+/// Largest coordinate accepted, which keeps the overlap map to a few million points
+pub const MAX_COORDINATE: u32 = 2047;
+
+fn parse_segments(s: &str) -> Result<Vec<Segment>, ParseError> {
+    let coordinate = |token: Token, expected: &str| match token.parse(expected)? {
+        c if c > MAX_COORDINATE => {
+            Err(token.error(&format!("{} up to {}", expected, MAX_COORDINATE)))
+        }
+        c => Ok(c),
+    };
+    let parse_point = |token: Token| -> Result<(u32, u32), ParseError> {
+        let xy = token.split_n(",", 2, "a point like 'x,y'")?;
+        Ok((coordinate(xy[0], "an x coordinate")?, coordinate(xy[1], "a y coordinate")?))
+    };
+    parsing::parse_input(s, |line| {
+        let arrow = line.token(1, "'->'")?;
+        if arrow.text != "->" {
+            return Err(arrow.error("'->'"));
+        }
+        line.end(3)?;
+        Ok(Segment {
+            from: parse_point(line.token(0, "a start point")?)?,
+            to: parse_point(line.token(2, "an end point")?)?,
+        })
+    })
+}
+
+pub const INPUT: &str = r"
 593,10 -> 593,98
 777,236 -> 964,236
 650,575 -> 476,575
@@ -568,5 +676,99 @@ fn get_vents() -> Vec<((u32, u32), (u32, u32))> {
 405,793 -> 405,183
 232,704 -> 232,389
 130,706 -> 130,657
-    ")
-}
\ No newline at end of file
+    ";
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXAMPLE: &str = r"
+            0,9 -> 5,9
+            8,0 -> 0,8
+            9,4 -> 3,4
+            2,2 -> 2,1
+            7,0 -> 7,4
+            6,4 -> 2,0
+            0,9 -> 2,9
+            3,4 -> 1,4
+            0,0 -> 8,8
+            5,5 -> 8,2
+    ";
+
+    #[test]
+    fn test_find_intersections() {
+        let segments = parse_segments(EXAMPLE).unwrap();
+        let overlaps = rasterize(&segments, SegmentKinds::AXIS_ALIGNED);
+        assert_eq!(overlaps.num_overlaps(), 5);
+        assert_eq!(
+            overlaps.to_string(),
+            "\
+.......1..
+..1....1..
+..1....1..
+.......1..
+.112111211
+..........
+..........
+..........
+..........
+222111....
+"
+        );
+    }
+
+    #[test]
+    fn test_find_intersections_with_diagonals() {
+        let segments = parse_segments(EXAMPLE).unwrap();
+        let overlaps = rasterize(&segments, SegmentKinds::ALL);
+        assert_eq!(overlaps.num_overlaps(), 12);
+        assert_eq!(overlaps.count(4, 4), 3);
+        assert_eq!(
+            overlaps.to_string(),
+            "\
+1.1....11.
+.111...2..
+..2.1.111.
+...1.2.2..
+.112313211
+...1.2....
+..1...1...
+.1.....1..
+1.......1.
+222111....
+"
+        );
+    }
+
+    #[test]
+    fn test_segment_points() {
+        let segment = |from, to| Segment { from, to };
+        assert_eq!(segment((9, 7), (7, 9)).orientation(), Orientation::Diagonal);
+        assert_eq!(
+            segment((9, 7), (7, 9)).points().collect::<Vec<_>>(),
+            vec![(9, 7), (8, 8), (7, 9)]
+        );
+        assert_eq!(segment((3, 4), (1, 4)).points().count(), 3);
+        assert_eq!(segment((0, 0), (2, 1)).orientation(), Orientation::Other);
+        assert_eq!(segment((0, 0), (2, 1)).points().count(), 0);
+
+        // only the segments drawn take up room on the map
+        let segments = [segment((0, 0), (2, 0)), segment((0, 0), (2000, 2000))];
+        let overlaps = rasterize(&segments, SegmentKinds::AXIS_ALIGNED);
+        assert_eq!(overlaps.to_string(), "111\n");
+        let overlaps = rasterize(&[segment((0, 0), (2000, 1))], SegmentKinds::ALL);
+        assert_eq!(overlaps.to_string(), "");
+    }
+
+    #[test]
+    fn test_parse_segments_errors() {
+        let err = parse_segments("0,9 -> 5,9\n8,0 => 0,8").unwrap_err();
+        assert_eq!((err.line, err.column, err.token.as_str()), (2, 5, "=>"));
+        let err = parse_segments("0,9 -> 5,x").unwrap_err();
+        assert_eq!((err.line, err.column, err.token.as_str()), (1, 10, "x"));
+        let err = parse_segments("0,9 -> 5").unwrap_err();
+        assert_eq!(err.expected, "a point like 'x,y'");
+        let err = parse_segments("0,0 -> 4000000000,0").unwrap_err();
+        assert_eq!((err.column, err.expected.as_str()), (8, "an x coordinate up to 2047"));
+    }
+}
@@ -1,47 +1,69 @@
-use crate::parsing;
+use crate::parsing::{self, ParseError};
+use crate::solution::Solution;
 
-pub fn run() {
-    let (_, num_lanternfish) = simulate_lanternfish(&get_lanternfish(), 80);
-    println!("Day  6: There will be {} lanternfish after 80 days", num_lanternfish);
+pub struct Day06 {
+    fish: [u64; 9],
+}
+
+impl Solution for Day06 {
+    type Answer1 = u64;
+    type Answer2 = u64;
+
+    fn parse(input: &str) -> Result<Self, ParseError> {
+        Ok(Self {
+            fish: parse_lanternfish(input)?,
+        })
+    }
+
+    fn part1(&self) -> u64 {
+        let (_, num_fish) = simulate_lanternfish(&self.fish, 80);
+        num_fish
+    }
+
+    fn part2(&self) -> u64 {
+        let (_, num_fish) = simulate_lanternfish(&self.fish, 256);
+        num_fish
+    }
 }
 
 /// Returns (number of fish by days until new gen, total number of fish)
 pub fn simulate_lanternfish(fish : &[u64;9], num_days: u32) -> ([u64;9], u64) {
-    let mut new_fish = [0;9];
-    for i in 0..9 { new_fish[i] = fish[i] }
+    let mut new_fish = *fish;
 
     for _ in 0..num_days {
         let num_reproducing = new_fish[0];
-        for i in 0..8 { new_fish[i] = new_fish[i+1]; }
-        new_fish[8] = num_reproducing;
+        new_fish.rotate_left(1);
         new_fish[6] += num_reproducing;
     }
 
-    let mut num_fish = 0;
-    for i in 0..9 { num_fish += new_fish[i] }
+    let num_fish = new_fish.iter().sum();
     (new_fish, num_fish)
 }
 
-fn parse_lanternfish(s: &str) -> [u64;9] {
+fn parse_lanternfish(s: &str) -> Result<[u64;9], ParseError> {
     let mut fish = [0;9];
-    parsing::parse_input(s, |parts| {
-        parts[0].split(",")
-            .map(|x| x.parse::<u32>().unwrap())
-            .for_each(|x| {
-                if x > 8 { panic!("Invalid fish! timer={}", x) }
-                fish[x as usize] += 1;
-            })
-    });
-    fish
+    parsing::parse_input(s, |line| {
+        for timer in line.token(0, "comma separated timers")?.split(",") {
+            let x = timer.parse::<usize>("a timer")?;
+            if x > 8 { return Err(timer.error("a timer between 0 and 8")) }
+            fish[x] += 1;
+        }
+        line.end(1)
+    })?;
+    Ok(fish)
 }
 
+pub const INPUT: &str = r"
+1,1,3,5,3,1,1,4,1,1,5,2,4,3,1,1,3,1,1,5,5,1,3,2,5,4,1,1,5,1,4,2,1,4,2,1,4,4,1,5,1,4,4,1,1,5,1,5,1,5,1,1,1,5,1,2,5,1,1,3,2,2,2,1,4,1,1,2,4,1,3,1,2,1,3,5,2,3,5,1,1,4,3,3,5,1,5,3,1,2,3,4,1,1,5,4,1,3,4,4,1,2,4,4,1,1,3,5,3,1,2,2,5,1,4,1,3,3,3,3,1,1,2,1,5,3,4,5,1,5,2,5,3,2,1,4,2,1,1,1,4,1,2,1,2,2,4,5,5,5,4,1,4,1,4,2,3,2,3,1,1,2,3,1,1,1,5,2,2,5,3,1,4,1,2,1,1,5,3,1,4,5,1,4,2,1,1,5,1,5,4,1,5,5,2,3,1,3,5,1,1,1,1,3,1,1,4,1,5,2,1,1,3,5,1,1,4,2,1,2,5,2,5,1,1,1,2,3,5,5,1,4,3,2,2,3,2,1,1,4,1,3,5,2,3,1,1,5,1,3,5,1,1,5,5,3,1,3,3,1,2,3,1,5,1,3,2,1,3,1,1,2,3,5,3,5,5,4,3,1,5,1,1,2,3,2,2,1,1,2,1,4,1,2,3,3,3,1,3,5
+    ";
+
 #[cfg(test)]
 mod test {
     use super::*;
 
     #[test]
     fn test_lanternfish() {
-        let fish = parse_lanternfish("3,4,3,1,2");
+        let fish = parse_lanternfish("3,4,3,1,2").unwrap();
         {
             let (_, num_fish) = simulate_lanternfish(&fish, 18);
             assert_eq!(num_fish, 26);
@@ -52,9 +74,3 @@ mod test {
         }
     }
 }
-
-fn get_lanternfish() -> [u64;9] {
-    parse_lanternfish(r"
-1,1,3,5,3,1,1,4,1,1,5,2,4,3,1,1,3,1,1,5,5,1,3,2,5,4,1,1,5,1,4,2,1,4,2,1,4,4,1,5,1,4,4,1,1,5,1,5,1,5,1,1,1,5,1,2,5,1,1,3,2,2,2,1,4,1,1,2,4,1,3,1,2,1,3,5,2,3,5,1,1,4,3,3,5,1,5,3,1,2,3,4,1,1,5,4,1,3,4,4,1,2,4,4,1,1,3,5,3,1,2,2,5,1,4,1,3,3,3,3,1,1,2,1,5,3,4,5,1,5,2,5,3,2,1,4,2,1,1,1,4,1,2,1,2,2,4,5,5,5,4,1,4,1,4,2,3,2,3,1,1,2,3,1,1,1,5,2,2,5,3,1,4,1,2,1,1,5,3,1,4,5,1,4,2,1,1,5,1,5,4,1,5,5,2,3,1,3,5,1,1,1,1,3,1,1,4,1,5,2,1,1,3,5,1,1,4,2,1,2,5,2,5,1,1,1,2,3,5,5,1,4,3,2,2,3,2,1,1,4,1,3,5,2,3,1,1,5,1,3,5,1,1,5,5,3,1,3,3,1,2,3,1,5,1,3,2,1,3,1,1,2,3,5,3,5,5,4,3,1,5,1,1,2,3,2,2,1,1,2,1,4,1,2,3,3,3,1,3,5
-    ")
-}
\ No newline at end of file
@@ -1,24 +1,36 @@
 use std::collections::HashMap;
 
-use crate::parsing;
+use crate::parsing::{self, ParseError};
+use crate::solution::Solution;
 
-pub fn run() {
-    let digit_counts = count_digit_segments(&get_digit_segments());
-    println!(
-        "Day  8: There will be {} 1s, {} 4s, {} 7s, {} 8s for a total of {}",
-        digit_counts[1],
-        digit_counts[4],
-        digit_counts[7],
-        digit_counts[8],
+/// Signal patterns and output values of a display
+type Entry = (Vec<String>, Vec<String>);
+
+pub struct Day08 {
+    entries: Vec<Entry>,
+}
+
+impl Solution for Day08 {
+    type Answer1 = u32;
+    type Answer2 = u32;
+
+    fn parse(input: &str) -> Result<Self, ParseError> {
+        Ok(Self {
+            entries: parse_digit_segments(input)?,
+        })
+    }
+
+    fn part1(&self) -> u32 {
+        let digit_counts = count_digit_segments(&self.entries);
         digit_counts[1] + digit_counts[4] + digit_counts[7] + digit_counts[8]
-    );
-    println!(
-        "      : Total of outputs={}",
-        decode_segments(&get_digit_segments()).iter().sum::<u32>()
-    );
+    }
+
+    fn part2(&self) -> u32 {
+        decode_segments(&self.entries).iter().sum()
+    }
 }
 
-pub fn count_digit_segments(d: &Vec<(Vec<String>, Vec<String>)>) -> [u32; 10] {
+pub fn count_digit_segments(d: &[Entry]) -> [u32; 10] {
     let mut digits = [0; 10];
     d.iter()
         .flat_map(|(_input, output)| output.iter())
@@ -33,7 +45,7 @@ pub fn count_digit_segments(d: &Vec<(Vec<String>, Vec<String>)>) -> [u32; 10] {
     digits
 }
 
-pub fn decode_segments(d: &Vec<(Vec<String>, Vec<String>)>) -> Vec<u32> {
+pub fn decode_segments(d: &[Entry]) -> Vec<u32> {
     //  0
     // 1 2
     //  3
@@ -126,7 +138,7 @@ pub fn decode_segments(d: &Vec<(Vec<String>, Vec<String>)>) -> Vec<u32> {
             });
             restrict_opts_based_on_found(&mut opts);
 
-            vec![
+            [
                 (3, 1), // disambiguate 0
                 (2, 5), // disambiguate 6
                 (4, 6), // disambiguate 9
@@ -180,69 +192,36 @@ pub fn decode_segments(d: &Vec<(Vec<String>, Vec<String>)>) -> Vec<u32> {
         .collect::<Vec<u32>>()
 }
 
-fn parse_digit_segments(s: &str) -> Vec<(Vec<String>, Vec<String>)> {
-    let mut entries = Vec::new();
-    parsing::parse_input(s, |parts| {
-        let mut parts = parts.iter().map(|s| s.to_string()).collect::<Vec<String>>();
-        if let Some(slash_at) = parts.iter().position(|p| p == "|") {
-            let mut output = parts.split_off(slash_at);
-            output.remove(0);
-            entries.push((parts, output));
-        }
-    });
-    entries
-}
+/// Signal patterns before the '|' on each line, one for each digit
+const PATTERNS: usize = 10;
+/// Digits of the output value after the '|'
+const OUTPUTS: usize = 4;
 
-#[cfg(test)]
-mod test {
-    use super::*;
-
-    #[test]
-    fn test_count_digit_segments() {
-        let digit_segments = parse_digit_segments(
-            r"
-            be cfbegad cbdgef fgaecd cgeb fdcge agebfd fecdb fabcd edb | fdgacbe cefdb cefbgd gcbe
-            edbfga begcd cbg gc gcadebf fbgde acbgfd abcde gfcbed gfec | fcgedb cgb dgebacf gc
-            fgaebd cg bdaec gdafb agbcfd gdcbef bgcad gfac gcb cdgabef | cg cg fdcagb cbg
-            fbegcd cbd adcefb dageb afcb bc aefdc ecdab fgdeca fcdbega | efabcd cedba gadfec cb
-            aecbfdg fbg gf bafeg dbefa fcge gcbea fcaegb dgceab fcbdga | gecf egdcabf bgf bfgea
-            fgeab ca afcebg bdacfeg cfaedg gcfdb baec bfadeg bafgc acf | gebdcfa ecba ca fadegcb
-            dbcfg fgd bdegcaf fgec aegbdf ecdfab fbedc dacgb gdcebf gf | cefg dcbef fcge gbcadfe
-            bdfegc cbegaf gecbf dfcage bdacg ed bedf ced adcbefg gebcd | ed bcgafe cdgba cbgef
-            egadfb cdbfeg cegd fecab cgb gbdefca cg fgcdab egfdb bfceg | gbdfcae bgc cg cgb
-            gcafb gcf dcaebfg ecagb gf abcdeg gaef cafbge fdbac fegbdc | fgae cfgab fg bagce
-        ",
-        );
-        let digits = count_digit_segments(&digit_segments);
-        assert_eq!(digits[1], 8);
-        assert_eq!(digits[4], 6);
-        assert_eq!(digits[7], 5);
-        assert_eq!(digits[8], 7);
-    }
-
-    #[test]
-    fn test_decode_segments() {
-        let digit_segments = parse_digit_segments(
-            r"
-            be cfbegad cbdgef fgaecd cgeb fdcge agebfd fecdb fabcd edb | fdgacbe cefdb cefbgd gcbe
-            edbfga begcd cbg gc gcadebf fbgde acbgfd abcde gfcbed gfec | fcgedb cgb dgebacf gc
-            fgaebd cg bdaec gdafb agbcfd gdcbef bgcad gfac gcb cdgabef | cg cg fdcagb cbg
-            fbegcd cbd adcefb dageb afcb bc aefdc ecdab fgdeca fcdbega | efabcd cedba gadfec cb
-            aecbfdg fbg gf bafeg dbefa fcge gcbea fcaegb dgceab fcbdga | gecf egdcabf bgf bfgea
-            fgeab ca afcebg bdacfeg cfaedg gcfdb baec bfadeg bafgc acf | gebdcfa ecba ca fadegcb
-            dbcfg fgd bdegcaf fgec aegbdf ecdfab fbedc dacgb gdcebf gf | cefg dcbef fcge gbcadfe
-            bdfegc cbegaf gecbf dfcage bdacg ed bedf ced adcbefg gebcd | ed bcgafe cdgba cbgef
-            egadfb cdbfeg cegd fecab cgb gbdefca cg fgcdab egfdb bfceg | gbdfcae bgc cg cgb
-            gcafb gcf dcaebfg ecagb gf abcdeg gaef cafbge fdbac fegbdc | fgae cfgab fg bagce
-        ",
-        );
-        assert_eq!(decode_segments(&digit_segments).iter().sum::<u32>(), 61229);
-    }
+fn parse_digit_segments(s: &str) -> Result<Vec<Entry>, ParseError> {
+    parsing::parse_input(s, |line| {
+        let slash_at = line
+            .tokens
+            .iter()
+            .position(|t| t.text == "|")
+            .ok_or_else(|| line.error("patterns and output separated by '|'"))?;
+        if slash_at != PATTERNS {
+            return Err(line.tokens[slash_at].error(&format!("'|' after {} patterns", PATTERNS)));
+        }
+        line.token(PATTERNS + OUTPUTS, &format!("{} output values", OUTPUTS))?;
+        line.end(PATTERNS + OUTPUTS + 1)?;
+        let mut parts = Vec::new();
+        for token in line.tokens[..slash_at].iter().chain(&line.tokens[slash_at + 1..]) {
+            if !token.text.chars().all(|c| ('a'..='g').contains(&c)) {
+                return Err(token.error("segments 'a' through 'g'"));
+            }
+            parts.push(token.text.to_string());
+        }
+        let output = parts.split_off(slash_at);
+        Ok((parts, output))
+    })
 }
 
-fn get_digit_segments() -> Vec<(Vec<String>, Vec<String>)> {
-    parse_digit_segments(
-        r"
+pub const INPUT: &str = r"
 cgaed gcdbfa gcfaed gfcde gadfceb cdbfeg acg eacf eabgd ca | agc efcgbd cag eacf
 ga ega edgfa cafed gabd cefagdb begfad ebdgf fcbega cbgdfe | bgdef fdgeb dgabfe gea
 ged eg acfgd fdceb cdbefa dgcfe cebfdg edcbga egbf ceadfbg | dfcge dacegbf gcdbaef fdceg
@@ -443,6 +422,64 @@ df abfce degbc cfd acgfed gbdf bacfegd efdcb badceg egfcbd | cebgd bgdf bceaf df
 bfdc eabdf ebcad fgead bcdeag fba bf bcedgfa cdebfa bcegaf | defba cfbd bdcf fabceg
 dbaefcg ec aec dfgbea cafeg aedcbg gacedf egadf gfcba cfde | eac efcd ec egcfda
 ecgfdab gcefb bcfa af cgfdbe aegbf ebadg eacgfd gbecaf afg | aedbg bafc acfb dgfbace
-    ",
-    )
+    ";
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_count_digit_segments() {
+        let digit_segments = parse_digit_segments(
+            r"
+            be cfbegad cbdgef fgaecd cgeb fdcge agebfd fecdb fabcd edb | fdgacbe cefdb cefbgd gcbe
+            edbfga begcd cbg gc gcadebf fbgde acbgfd abcde gfcbed gfec | fcgedb cgb dgebacf gc
+            fgaebd cg bdaec gdafb agbcfd gdcbef bgcad gfac gcb cdgabef | cg cg fdcagb cbg
+            fbegcd cbd adcefb dageb afcb bc aefdc ecdab fgdeca fcdbega | efabcd cedba gadfec cb
+            aecbfdg fbg gf bafeg dbefa fcge gcbea fcaegb dgceab fcbdga | gecf egdcabf bgf bfgea
+            fgeab ca afcebg bdacfeg cfaedg gcfdb baec bfadeg bafgc acf | gebdcfa ecba ca fadegcb
+            dbcfg fgd bdegcaf fgec aegbdf ecdfab fbedc dacgb gdcebf gf | cefg dcbef fcge gbcadfe
+            bdfegc cbegaf gecbf dfcage bdacg ed bedf ced adcbefg gebcd | ed bcgafe cdgba cbgef
+            egadfb cdbfeg cegd fecab cgb gbdefca cg fgcdab egfdb bfceg | gbdfcae bgc cg cgb
+            gcafb gcf dcaebfg ecagb gf abcdeg gaef cafbge fdbac fegbdc | fgae cfgab fg bagce
+        ",
+        ).unwrap();
+        let digits = count_digit_segments(&digit_segments);
+        assert_eq!(digits[1], 8);
+        assert_eq!(digits[4], 6);
+        assert_eq!(digits[7], 5);
+        assert_eq!(digits[8], 7);
+    }
+
+    #[test]
+    fn test_decode_segments() {
+        let digit_segments = parse_digit_segments(
+            r"
+            be cfbegad cbdgef fgaecd cgeb fdcge agebfd fecdb fabcd edb | fdgacbe cefdb cefbgd gcbe
+            edbfga begcd cbg gc gcadebf fbgde acbgfd abcde gfcbed gfec | fcgedb cgb dgebacf gc
+            fgaebd cg bdaec gdafb agbcfd gdcbef bgcad gfac gcb cdgabef | cg cg fdcagb cbg
+            fbegcd cbd adcefb dageb afcb bc aefdc ecdab fgdeca fcdbega | efabcd cedba gadfec cb
+            aecbfdg fbg gf bafeg dbefa fcge gcbea fcaegb dgceab fcbdga | gecf egdcabf bgf bfgea
+            fgeab ca afcebg bdacfeg cfaedg gcfdb baec bfadeg bafgc acf | gebdcfa ecba ca fadegcb
+            dbcfg fgd bdegcaf fgec aegbdf ecdfab fbedc dacgb gdcebf gf | cefg dcbef fcge gbcadfe
+            bdfegc cbegaf gecbf dfcage bdacg ed bedf ced adcbefg gebcd | ed bcgafe cdgba cbgef
+            egadfb cdbfeg cegd fecab cgb gbdefca cg fgcdab egfdb bfceg | gbdfcae bgc cg cgb
+            gcafb gcf dcaebfg ecagb gf abcdeg gaef cafbge fdbac fegbdc | fgae cfgab fg bagce
+        ",
+        ).unwrap();
+        assert_eq!(decode_segments(&digit_segments).iter().sum::<u32>(), 61229);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let err = parse_digit_segments("ab cd | ef").unwrap_err();
+        assert_eq!((err.column, err.token.as_str()), (7, "|"));
+        let patterns = "be cfbegad cbdgef fgaecd cgeb fdcge agebfd fecdb fabcd edb";
+        let err = parse_digit_segments(&format!("{} | fdgacbe cefdb", patterns)).unwrap_err();
+        assert_eq!((err.column, err.expected.as_str()), (75, "4 output values"));
+        let err = parse_digit_segments(&format!("{} | ab ab ab ab ab", patterns)).unwrap_err();
+        assert_eq!((err.column, err.expected.as_str()), (74, "the end of the line"));
+        let err = parse_digit_segments(&format!("{} | ab ab | ab", patterns)).unwrap_err();
+        assert_eq!((err.column, err.token.as_str()), (68, "|"));
+    }
 }
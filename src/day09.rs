@@ -1,80 +1,121 @@
-use crate::parsing;
+use std::fmt;
 
-pub fn run() {
-    println!(
-        "Day  9: Total risk of low points={}",
-        find_low_points(&get_height_map())
+use crate::grid::{self, Grid, Point};
+use crate::parsing::ParseError;
+use crate::solution::Solution;
+
+pub struct Day09 {
+    height_map: Grid<u8>,
+}
+
+impl Solution for Day09 {
+    type Answer1 = u32;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Self, ParseError> {
+        Ok(Self {
+            height_map: parse_height_map(input)?,
+        })
+    }
+
+    fn part1(&self) -> u32 {
+        find_low_points(&self.height_map)
             .iter()
-            .map(|(_, y)| y)
-            .sum::<u32>()
-    );
+            .map(|(_, risk)| risk)
+            .sum()
+    }
+
+    fn part2(&self) -> usize {
+        find_basins(&self.height_map).largest_product(3)
+    }
 }
 
 // find low points in input, return point and its risk
-pub fn find_low_points(d: &Vec<Vec<u8>>) -> Vec<((u32, u32), u32)> {
-    if d.is_empty() {
-        return Vec::new();
-    }
-    let calc_low_point_risk = |x: usize, y: usize| -> Option<u32> {
-        if x > 0 && d[x][y] >= d[x - 1][y] {
-            return None;
-        }
-        if x < d.len() - 1 && d[x][y] >= d[x + 1][y] {
-            return None;
-        }
-        if y > 0 && d[x][y] >= d[x][y - 1] {
-            return None;
-        }
-        if y < d[0].len() - 1 && d[x][y] >= d[x][y + 1] {
-            return None;
-        }
-        Some(d[x][y] as u32 + 1)
-    };
-    (0..d.len())
-        .flat_map(|x| (0..d[x].len()).map(move |y| (x, y)))
-        .filter_map(|(x, y)| match calc_low_point_risk(x, y) {
-            Some(risk) => Some(((x as u32, y as u32), risk)),
-            None => None,
-        })
+pub fn find_low_points(d: &Grid<u8>) -> Vec<(Point, u32)> {
+    d.iter()
+        .filter(|(point, height)| d.neighbors4(*point).all(|n| d[n] > **height))
+        .map(|(point, height)| (point, *height as u32 + 1))
         .collect()
 }
 
-fn parse_height_map(s: &str) -> Vec<Vec<u8>> {
-    let mut rows = Vec::new();
-    parsing::parse_input(s, |parts| {
-        rows.push(
-            parts[0]
-                .chars()
-                .map(|c| c.to_string().parse::<u8>().unwrap())
-                .collect(),
-        );
-    });
-    rows
+/// A region of the height map which drains to a single low point
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Basin {
+    pub low_point: Point,
+    pub cells: Vec<Point>,
 }
 
-#[cfg(test)]
-mod test {
-    use super::*;
+impl Basin {
+    pub fn size(&self) -> usize {
+        self.cells.len()
+    }
+}
 
-    #[test]
-    fn test_find_low_points() {
-        let height_map = parse_height_map(
-            r"
-            2199943210
-            3987894921
-            9856789892
-            8767896789
-            9899965678
-        ",
-        );
-        let low_points = find_low_points(&height_map);
-        assert_eq!(low_points.iter().map(|(_, y)| y).sum::<u32>(), 15);
+/// Every basin of a height map, along with which basin each cell belongs to
+pub struct BasinMap {
+    pub basins: Vec<Basin>,
+    labels: Grid<Option<usize>>,
+}
+
+impl BasinMap {
+    /// Index into `basins` of the basin containing a cell, or None for the height 9 walls
+    #[cfg(test)]
+    pub fn basin_at(&self, point: Point) -> Option<usize> {
+        self.labels.get(point).copied().flatten()
+    }
+
+    /// Product of the sizes of the `n` largest basins
+    pub fn largest_product(&self, n: usize) -> usize {
+        let mut sizes = self.basins.iter().map(|b| b.size()).collect::<Vec<usize>>();
+        sizes.sort_unstable_by(|a, b| b.cmp(a));
+        sizes.iter().take(n).product()
     }
 }
 
-fn get_height_map() -> Vec<Vec<u8>> {
-    parse_height_map(
-        r"
+impl fmt::Display for BasinMap {
+    /// Labels each basin with a letter, cycling through the alphabet, and walls with '#'
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let labels = self.labels.map(|label| match label {
+            Some(basin) => (b'a' + (basin % 26) as u8) as char,
+            None => '#',
+        });
+        write!(f, "{}", labels)
+    }
+}
+
+/// Flood fills the height map, assigning every cell other than a 9 to a basin
+pub fn find_basins(d: &Grid<u8>) -> BasinMap {
+    let mut labels = Grid::new(d.width(), d.height(), None);
+    let mut basins = Vec::new();
+    for start in d.points() {
+        if d[start] == 9 || labels[start].is_some() {
+            continue;
+        }
+        let label = basins.len();
+        let mut cells = Vec::new();
+        let mut stack = vec![start];
+        labels[start] = Some(label);
+        while let Some(point) = stack.pop() {
+            cells.push(point);
+            for next in d.neighbors4(point) {
+                if d[next] != 9 && labels[next].is_none() {
+                    labels[next] = Some(label);
+                    stack.push(next);
+                }
+            }
+        }
+        cells.sort_unstable_by_key(|(x, y)| (*y, *x));
+        let low_point = *cells.iter().min_by_key(|point| d[**point]).unwrap();
+        basins.push(Basin { low_point, cells });
+    }
+    BasinMap { basins, labels }
+}
+
+fn parse_height_map(s: &str) -> Result<Grid<u8>, ParseError> {
+    grid::parse_digits(s, "a row of heights")
+}
+
+pub const INPUT: &str = r"
 9876567896542101249889965434567898765698785435678989898897654789424901245699887678932398943999888667
 5995456976543294398767896223456789654988654523599976677789765678919893456989776587891987899898765456
 6976577897664989985458989012345678969876543212388965435678996789909789579876543456789996789789894347
@@ -175,6 +216,46 @@ fn get_height_map() -> Vec<Vec<u8>> {
 9499998767899895678954349876456997889954334589979997598767894943767899865410387899985396543210345789
 5323987756789987989765212989567898996563125678989987679878943432356789876521239999876987654421256895
 6212976545678998999986433497678999765431016789990198789989432101245678989432445689989998765632867934
-    ",
-    )
+    ";
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXAMPLE: &str = r"
+            2199943210
+            3987894921
+            9856789892
+            8767896789
+            9899965678
+    ";
+
+    #[test]
+    fn test_find_low_points() {
+        let height_map = parse_height_map(EXAMPLE).unwrap();
+        let low_points = find_low_points(&height_map);
+        assert_eq!(low_points.iter().map(|(_, y)| y).sum::<u32>(), 15);
+    }
+
+    #[test]
+    fn test_find_basins() {
+        let height_map = parse_height_map(EXAMPLE).unwrap();
+        let basins = find_basins(&height_map);
+        let mut sizes = basins.basins.iter().map(|b| (b.low_point, b.size())).collect::<Vec<_>>();
+        sizes.sort_unstable();
+        assert_eq!(sizes, vec![((1, 0), 3), ((2, 2), 14), ((6, 4), 9), ((9, 0), 9)]);
+        assert_eq!(basins.largest_product(3), 1134);
+        assert_eq!(basins.basin_at((0, 0)), basins.basin_at((0, 1)));
+        assert_eq!(basins.basin_at((2, 0)), None);
+        assert_eq!(
+            basins.to_string(),
+            "\
+aa###bbbbb
+a#ccc#b#bb
+#ccccc#d#b
+ccccc#ddd#
+#c###ddddd
+"
+        );
+    }
 }
@@ -1,102 +1,160 @@
-use std::borrow::Cow;
-use std::collections::VecDeque;
+use std::fmt;
 
-use crate::parsing;
+use crate::parsing::{self, ParseError};
+use crate::solution::Solution;
 
-pub fn run() {
-    let illegal_closings = find_illegal_closings(&get_chunk_boundaries());
-    println!(
-        "Day 10: Illegal closings score={}",
-        score_illegal_closings(&illegal_closings)
-    );
+pub struct Day10 {
+    lines: Vec<String>,
 }
 
-pub fn find_first_illegal_closing(s: &str) -> Option<char> {
-    let mut stack = VecDeque::new();
+impl Solution for Day10 {
+    type Answer1 = u64;
+    type Answer2 = MedianScore;
 
-    s.chars()
-        .filter_map(|c| match c {
-            '(' => {
-                stack.push_back(')');
-                None
-            }
-            '[' => {
-                stack.push_back(']');
-                None
-            }
-            '{' => {
-                stack.push_back('}');
-                None
-            }
-            '<' => {
-                stack.push_back('>');
-                None
-            }
-            _ => match stack.pop_back() {
-                Some(expected) => {
-                    if c == expected {
-                        None
-                    } else {
-                        Some(c)
-                    }
-                }
-                None => Some(c),
-            },
+    fn parse(input: &str) -> Result<Self, ParseError> {
+        Ok(Self {
+            lines: parse_chunk_boundaries(input)?,
         })
-        .nth(0)
+    }
+
+    fn part1(&self) -> u64 {
+        score_syntax_errors(&analyze_lines(&self.lines))
+    }
+
+    fn part2(&self) -> MedianScore {
+        MedianScore(median_autocomplete_score(&analyze_lines(&self.lines)))
+    }
 }
 
-pub fn find_illegal_closings(d: &Vec<Cow<str>>) -> Vec<Option<char>> {
-    d.iter().map(|s| find_first_illegal_closing(s)).collect()
+/// The middle autocomplete score, if any line is incomplete
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MedianScore(pub Option<u64>);
+
+impl fmt::Display for MedianScore {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.0 {
+            Some(score) => write!(f, "{}", score),
+            None => write!(f, "no incomplete line"),
+        }
+    }
 }
 
-pub fn score_illegal_closings(d: &Vec<Option<char>>) -> u64 {
-    d.iter()
-        .map(|c| match c {
-            Some(c) => match c {
+/// Outcome of checking a line's chunks
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LineStatus {
+    Valid,
+    /// A closing character at `position` (0-based) didn't match the innermost open chunk,
+    /// or there was no open chunk to close
+    Corrupted {
+        position: usize,
+        expected: Option<char>,
+        found: char,
+    },
+    /// Some chunks are still open, and `completion` closes them all
+    Incomplete { completion: String },
+}
+
+impl LineStatus {
+    pub fn syntax_error_score(&self) -> u64 {
+        match self {
+            LineStatus::Corrupted { found, .. } => match found {
                 ')' => 3,
                 ']' => 57,
                 '}' => 1197,
                 '>' => 25137,
                 _ => 0,
             },
-            None => 0,
-        })
-        .sum()
-}
+            _ => 0,
+        }
+    }
 
-fn parse_chunk_boundaries(s: &str) -> Vec<Cow<str>> {
-    parsing::parse_lines(s)
+    pub fn autocomplete_score(&self) -> Option<u64> {
+        match self {
+            LineStatus::Incomplete { completion } => Some(score_completion(completion)),
+            _ => None,
+        }
+    }
 }
 
-#[cfg(test)]
-mod test {
-    use super::*;
+fn closing(c: char) -> Option<char> {
+    match c {
+        '(' => Some(')'),
+        '[' => Some(']'),
+        '{' => Some('}'),
+        '<' => Some('>'),
+        _ => None,
+    }
+}
 
-    #[test]
-    fn test_score_illegal_closings() {
-        let chunk_boundaries = parse_chunk_boundaries(
-            r"
-            [({(<(())[]>[[{[]{<()<>>
-            [(()[<>])]({[<{<<[]>>(
-            {([(<{}[<>[]}>{[]{[(<()>
-            (((({<>}<{<{<>}{[]{[]{}
-            [[<[([]))<([[{}[[()]]]
-            [{[{({}]{}}([{[{{{}}([]
-            {<[[]]>}<{[{[{[]{()[[[]
-            [<(<(<(<{}))><([]([]()
-            <{([([[(<>()){}]>(<<{{
-            <{([{{}}[<[[[<>{}]]]>[]]
-        ",
-        );
-        let illegal_closings = find_illegal_closings(&chunk_boundaries);
-        assert_eq!(score_illegal_closings(&illegal_closings), 26397);
+pub fn analyze_line(s: &str) -> LineStatus {
+    let mut stack = Vec::new();
+    for (position, c) in s.chars().enumerate() {
+        match closing(c) {
+            Some(close) => stack.push(close),
+            None => {
+                let expected = stack.pop();
+                if expected != Some(c) {
+                    return LineStatus::Corrupted {
+                        position,
+                        expected,
+                        found: c,
+                    };
+                }
+            }
+        }
+    }
+    if stack.is_empty() {
+        LineStatus::Valid
+    } else {
+        LineStatus::Incomplete {
+            completion: stack.iter().rev().collect(),
+        }
     }
 }
 
-fn get_chunk_boundaries() -> Vec<Cow<'static, str>> {
-    parse_chunk_boundaries(
-        r"
+pub fn analyze_lines(d: &[String]) -> Vec<LineStatus> {
+    d.iter().map(|s| analyze_line(s)).collect()
+}
+
+pub fn score_syntax_errors(d: &[LineStatus]) -> u64 {
+    d.iter().map(|status| status.syntax_error_score()).sum()
+}
+
+pub fn score_completion(completion: &str) -> u64 {
+    completion.chars().fold(0, |score, c| {
+        score * 5
+            + match c {
+                ')' => 1,
+                ']' => 2,
+                '}' => 3,
+                '>' => 4,
+                _ => 0,
+            }
+    })
+}
+
+/// The middle autocomplete score of the incomplete lines, if there are any
+pub fn median_autocomplete_score(d: &[LineStatus]) -> Option<u64> {
+    let mut scores = d
+        .iter()
+        .filter_map(|status| status.autocomplete_score())
+        .collect::<Vec<u64>>();
+    scores.sort_unstable();
+    scores.get(scores.len() / 2).copied()
+}
+
+fn parse_chunk_boundaries(s: &str) -> Result<Vec<String>, ParseError> {
+    parsing::parse_input(s, |line| {
+        let chunks = line.token(0, "a line of chunks")?;
+        line.end(1)?;
+        match chunks.chars().find(|c| !"()[]{}<>".contains(c.text)) {
+            Some(c) => Err(c.error("one of ()[]{}<>")),
+            None => Ok(chunks.text.to_string()),
+        }
+    })
+}
+
+pub const INPUT: &str = r"
 [({<(({{(([([[{}{}](<>())][<(){}>[[][]]]){{(<>{})<{}()>}}](<<<()<>><()<>>>([<>[]])><{<[][]
 <<([{([<([{<<{[]<>}([]{})>><{(<>{})([][])}[[{}]((){})]>}([[([]<>)[<><>)]{<{}[]>{[]()}}]<<[{}{}]<<>{}>>{[{}<>]
 {<<[(<{<({[{[{[][]}{[]{}}](<[]<>>[<><>])}]}[<{(<<>{}>(()()))<(<>{})(<>())>}>{{{{()()}{<>}}}}])><<{{<{[
@@ -203,6 +261,65 @@ fn get_chunk_boundaries() -> Vec<Cow<'static, str>> {
 <<{((([<[(<[{<[]()>}{(())<()<>>}]>[{<[<>()]{()<>}>[{<><>}{[][]}]}{[<{}{}>({}<>)]{{[]()>{[]<>
 [[[{[[[{{<(<{{[]<>}[<><>]}({()()}[()[]])>[((<>[])(<>)){{()<>}([][])}])[{{{[]<>}<()[])}({<>[]})}(({
 {<<<(<(((({[[([])([][])]<<[]<>>>]}{{<{{}[]}(<>[])><{[][]}[[]{}]>}<{<()[]>}<{[]{}}{()<>}>>})){<<<[{[]<>}[[]
-    ",
-    )
+    ";
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXAMPLE: &str = r"
+            [({(<(())[]>[[{[]{<()<>>
+            [(()[<>])]({[<{<<[]>>(
+            {([(<{}[<>[]}>{[]{[(<()>
+            (((({<>}<{<{<>}{[]{[]{}
+            [[<[([]))<([[{}[[()]]]
+            [{[{({}]{}}([{[{{{}}([]
+            {<[[]]>}<{[{[{[]{()[[[]
+            [<(<(<(<{}))><([]([]()
+            <{([([[(<>()){}]>(<<{{
+            <{([{{}}[<[[[<>{}]]]>[]]
+    ";
+
+    #[test]
+    fn test_score_illegal_closings() {
+        let chunk_boundaries = parse_chunk_boundaries(EXAMPLE).unwrap();
+        let statuses = analyze_lines(&chunk_boundaries);
+        assert_eq!(score_syntax_errors(&statuses), 26397);
+        assert_eq!(
+            statuses[2],
+            LineStatus::Corrupted {
+                position: 12,
+                expected: Some(']'),
+                found: '}',
+            }
+        );
+    }
+
+    #[test]
+    fn test_autocomplete() {
+        let chunk_boundaries = parse_chunk_boundaries(EXAMPLE).unwrap();
+        let statuses = analyze_lines(&chunk_boundaries);
+        assert_eq!(
+            statuses[0],
+            LineStatus::Incomplete {
+                completion: "}}]])})]".to_string()
+            }
+        );
+        assert_eq!(statuses[0].autocomplete_score(), Some(288957));
+        assert_eq!(statuses[2].autocomplete_score(), None);
+        assert_eq!(score_completion("])}>"), 294);
+        assert_eq!(median_autocomplete_score(&statuses), Some(288957));
+        let day = Day10::parse("[<>({}){}[([])<>]]\n())").unwrap();
+        assert_eq!(day.part2().to_string(), "no incomplete line");
+
+        assert_eq!(analyze_line("[<>({}){}[([])<>]]"), LineStatus::Valid);
+        assert_eq!(
+            analyze_line("())"),
+            LineStatus::Corrupted {
+                position: 2,
+                expected: None,
+                found: ')',
+            }
+        );
+    }
 }
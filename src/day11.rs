@@ -1,116 +1,157 @@
-use crate::parsing;
-use std::collections::HashSet;
-
-pub fn run() {
-    let octopi = Octopi::new(parse_energies(
-        r"
-        7313511551
-        3724855867
-        2374331571
-        4438213437
-        6511566287
-        6727245532
-        3736868662
-        2348138263
-        2417483121
-        8812617112
-    ",
-    ));
-    println!(
-        "Day 11: After 100 days there have been {} flashes",
-        octopi.take(100).sum::<usize>()
-    );
+use crate::grid::{self, Grid, Point};
+use crate::parsing::{self, ParseError};
+use crate::solution::Solution;
+use std::collections::{HashSet, VecDeque};
+use std::fmt;
+
+pub struct Day11 {
+    energies: Grid<u8>,
+}
+
+impl Solution for Day11 {
+    type Answer1 = usize;
+    type Answer2 = FirstSync;
+
+    fn parse(input: &str) -> Result<Self, ParseError> {
+        Ok(Self {
+            energies: parse_energies(input)?,
+        })
+    }
+
+    fn part1(&self) -> usize {
+        Octopi::new(self.energies.clone()).take(100).sum()
+    }
+
+    fn part2(&self) -> FirstSync {
+        FirstSync(Octopi::new(self.energies.clone()).first_sync(MAX_SYNC_STEPS))
+    }
+}
+
+/// Steps to simulate while looking for a synchronized flash before giving up
+pub const MAX_SYNC_STEPS: usize = 100_000;
+
+/// The step on which every octopus first flashed at once, if they ever did
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FirstSync(pub Option<usize>);
+
+impl fmt::Display for FirstSync {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.0 {
+            Some(step) => write!(f, "{}", step),
+            None => write!(f, "no synchronized flash"),
+        }
+    }
+}
+
+/// The state of the octopi after a step
+#[cfg(test)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Step {
+    /// 1-based number of the step
+    pub number: usize,
+    pub energies: Grid<u8>,
+    /// Each octopus that flashed during the step
+    pub flashed: HashSet<Point>,
 }
 
 pub struct Octopi {
-    energies: Vec<Vec<u8>>,
+    energies: Grid<u8>,
+    steps: usize,
 }
 
 impl Octopi {
-    pub fn new(energies: Vec<Vec<u8>>) -> Octopi {
-        Self { energies }
+    pub fn new(energies: Grid<u8>) -> Octopi {
+        Self { energies, steps: 0 }
     }
-}
 
-impl Iterator for Octopi {
-    type Item = usize; // number of flashes in this iteration
-    fn next(&mut self) -> Option<Self::Item> {
-        self.energies
-            .iter_mut()
-            .flat_map(|row| row.iter_mut())
-            .for_each(|e| *e += 1);
-        let mut who_flashed = HashSet::new();
-        loop {
-            let mut bump_neighbors = Vec::new();
-            let mut num_flashed_in_step = 0;
-            self.energies
-                .iter()
-                .enumerate()
-                .flat_map(|(row, entries)| {
-                    entries
-                        .iter()
-                        .enumerate()
-                        .map(move |(col, e)| (row, col, e))
-                })
-                .filter(|(_row, _col, e)| **e >= 10)
-                .for_each(|(row, col, _e)| {
-                    if who_flashed.contains(&(row, col)) {
-                        return;
+    /// Advances one step and returns a snapshot of it
+    #[cfg(test)]
+    pub fn step(&mut self) -> Step {
+        let flashed = self.advance().into_iter().collect();
+        Step {
+            number: self.steps,
+            energies: self.energies.clone(),
+            flashed,
+        }
+    }
+
+    /// Steps until every octopus flashes at once, returning that step's number, or None if
+    /// that doesn't happen within `max_steps` or the octopi fall into a cycle without it
+    pub fn first_sync(&mut self, max_steps: usize) -> Option<usize> {
+        let num_octopi = self.energies.width() * self.energies.height();
+        let mut seen = HashSet::new();
+        for _ in 0..max_steps {
+            if self.advance().len() == num_octopi {
+                return Some(self.steps);
+            }
+            if !seen.insert(self.energies.clone()) {
+                return None;
+            }
+        }
+        None
+    }
+
+    /// Runs one step, returning each octopus that flashed in the order they did
+    fn advance(&mut self) -> Vec<Point> {
+        self.steps += 1;
+        let mut queue = VecDeque::new();
+        for point in self.energies.points() {
+            self.energies[point] += 1;
+            if self.energies[point] > 9 {
+                queue.push_back(point);
+            }
+        }
+
+        // an octopus is queued exactly once, when its energy first goes over 9
+        let mut flashed = Vec::new();
+        while let Some(point) = queue.pop_front() {
+            flashed.push(point);
+            for neighbor in self.energies.neighbors8(point) {
+                let e = &mut self.energies[neighbor];
+                if *e <= 9 {
+                    *e += 1;
+                    if *e > 9 {
+                        queue.push_back(neighbor);
                     }
-                    who_flashed.insert((row, col));
-                    num_flashed_in_step += 1;
-                    let row = row as i32;
-                    let col = col as i32;
-                    let mut neighbors = Vec::new();
-                    neighbors.push((row - 1, col - 1));
-                    neighbors.push((row - 1, col));
-                    neighbors.push((row - 1, col + 1));
-                    neighbors.push((row, col - 1));
-                    neighbors.push((row, col));
-                    neighbors.push((row, col + 1));
-                    neighbors.push((row + 1, col - 1));
-                    neighbors.push((row + 1, col));
-                    neighbors.push((row + 1, col + 1));
-                    neighbors
-                        .iter()
-                        .filter(|(r, c)| {
-                            *r >= 0
-                                && *r < self.energies.len() as i32
-                                && *c >= 0
-                                && *c < self.energies[*r as usize].len() as i32
-                        })
-                        .for_each(|(r, c)| bump_neighbors.push((*r as usize, *c as usize)));
-                });
-            bump_neighbors
-                .iter()
-                .for_each(|(row, col)| self.energies[*row][*col] += 1);
-            if num_flashed_in_step <= 0 {
-                break;
+                }
             }
         }
 
-        self.energies
-            .iter_mut()
-            .flat_map(|entries| entries.iter_mut())
-            .filter(|e| **e > 9)
-            .for_each(|e| *e = 0);
-        Some(who_flashed.len())
+        for point in &flashed {
+            self.energies[*point] = 0;
+        }
+        flashed
     }
 }
 
-fn parse_energies(s: &str) -> Vec<Vec<u8>> {
-    let mut rows = Vec::new();
-    parsing::parse_input(s, |parts| {
-        rows.push(
-            parts[0]
-                .chars()
-                .map(|c| c.to_string().parse::<u8>().unwrap())
-                .collect(),
-        );
-    });
-    rows
+impl Iterator for Octopi {
+    type Item = usize; // number of flashes in this iteration
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.advance().len())
+    }
+}
+
+fn parse_energies(s: &str) -> Result<Grid<u8>, ParseError> {
+    let energies = grid::parse_digits(s, "a row of energies")?;
+    match energies.height() {
+        0 => Err(parsing::end_of_input(s, "a row of energies")),
+        _ => Ok(energies),
+    }
 }
 
+pub const INPUT: &str = r"
+7313511551
+3724855867
+2374331571
+4438213437
+6511566287
+6727245532
+3736868662
+2348138263
+2417483121
+8812617112
+    ";
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -125,16 +166,13 @@ mod test {
         19991
         11111
         ",
-        ));
+        ).unwrap());
 
         assert_eq!(octopi.next(), Some(9));
         assert_eq!(octopi.next(), Some(0));
     }
 
-    #[test]
-    fn test_count_octopi_flashes_large() {
-        let mut octopi = Octopi::new(parse_energies(
-            r"
+    const EXAMPLE: &str = r"
             5483143223
             2745854711
             5264556173
@@ -145,11 +183,43 @@ mod test {
             6882881134
             4846848554
             5283751526
-        ",
-        ));
+    ";
+
+    #[test]
+    fn test_count_octopi_flashes_large() {
+        let mut octopi = Octopi::new(parse_energies(EXAMPLE).unwrap());
         assert_eq!(octopi.next(), Some(0));
         assert_eq!(octopi.next(), Some(35));
         assert_eq!(octopi.next(), Some(45));
         assert_eq!(octopi.take(97).sum::<usize>(), 1656 - 45 - 35);
     }
+
+    #[test]
+    fn test_step_snapshots() {
+        let mut octopi = Octopi::new(parse_energies("11111\n19991\n19191\n19991\n11111").unwrap());
+        let step = octopi.step();
+        assert_eq!(step.number, 1);
+        assert_eq!(
+            step.energies,
+            parse_energies("34543\n40004\n50005\n40004\n34543").unwrap()
+        );
+        assert_eq!(step.flashed.len(), 9);
+        assert!(step.flashed.contains(&(2, 2)));
+        assert!(!step.flashed.contains(&(0, 0)));
+        assert_eq!(octopi.step().number, 2);
+    }
+
+    #[test]
+    fn test_first_sync() {
+        let mut octopi = Octopi::new(parse_energies(EXAMPLE).unwrap());
+        assert_eq!(octopi.first_sync(MAX_SYNC_STEPS), Some(195));
+
+        let mut octopi = Octopi::new(parse_energies(EXAMPLE).unwrap());
+        assert_eq!(octopi.first_sync(100), None);
+
+        assert_eq!(Day11::parse(EXAMPLE).unwrap().part2().to_string(), "195");
+        assert_eq!(FirstSync(None).to_string(), "no synchronized flash");
+        let err = parse_energies("\n  \n").unwrap_err();
+        assert_eq!((err.line, err.expected.as_str()), (1, "a row of energies"));
+    }
 }
@@ -1,152 +1,285 @@
-use crate::parsing;
-use std::collections::HashMap;
-
-pub fn run() {
-    let caves = Caves::new(parse_caves(
-        r"
-        re-js
-        qx-CG
-        start-js
-        start-bj
-        qx-ak
-        js-bj
-        ak-re
-        CG-ak
-        js-CG
-        bj-re
-        ak-lg
-        lg-CG
-        qx-re
-        WP-ak
-        WP-end
-        re-lg
-        end-ak
-        WP-re
-        bj-CG
-        qx-start
-        bj-WP
-        JG-lg
-        end-lg
-        lg-iw
-    ",
-    ));
-
-    println!(
-        "Day 12: There are {} ways through the cave",
-        caves.walk("start", "end").len()
-    );
-    /* expensive :(
-    println!(
-        "      : There are {} ways to walk through the cave with up to 1 small cave revisited",
-        caves.walk_leisurely("start", "end").len()
-    );
-    */
+use crate::parsing::{self, ParseError};
+use crate::solution::Solution;
+use std::collections::{HashMap, HashSet};
+
+pub struct Day12 {
+    caves: Caves,
 }
 
+impl Solution for Day12 {
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Self, ParseError> {
+        Ok(Self {
+            caves: Caves::new(parse_caves(input)?),
+        })
+    }
+
+    fn part1(&self) -> usize {
+        self.caves.count_paths("start", "end", SmallCaves::VisitOnce)
+    }
+
+    fn part2(&self) -> usize {
+        self.caves.count_paths("start", "end", SmallCaves::RevisitOne)
+    }
+}
+
+/// How often small caves may be visited on a single path
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SmallCaves {
+    VisitOnce,
+    /// A single small cave (other than the start) may be visited twice
+    RevisitOne,
+}
+
+/// Most distinct caves a map can have, so a set of them fits in a bitmask
+pub const MAX_CAVES: usize = 64;
+
+/// The cave system, with each cave's name interned as its index
 pub struct Caves {
-    connections: HashMap<String, Vec<String>>,
+    names: Vec<String>,
+    ids: HashMap<String, usize>,
+    small: u64,
+    connections: Vec<Vec<usize>>,
 }
 
 impl Caves {
+    /// Panics with more than `MAX_CAVES` caves, which `parse_caves` never returns
     pub fn new(d: Vec<(String, String)>) -> Self {
-        let mut connections = HashMap::<String, Vec<String>>::new();
-        d.iter()
-            .flat_map(|(l, r)| vec![(l, r), (r, l)])
-            .for_each(|(from, to)| {
-                connections
-                    .entry(from.clone())
-                    .or_default()
-                    .push(to.clone());
-            });
-        Self { connections }
+        let mut caves = Self {
+            names: Vec::new(),
+            ids: HashMap::new(),
+            small: 0,
+            connections: Vec::new(),
+        };
+        for (l, r) in &d {
+            let (l, r) = (caves.intern(l), caves.intern(r));
+            caves.connections[l].push(r);
+            caves.connections[r].push(l);
+        }
+        caves
     }
 
-    pub fn can_visit(
-        node: &str,
-        crumbs: &Vec<String>,
-        start: &str,
-        end: &str,
-        max_small_revisits: u32,
-    ) -> bool {
-        let mut num_visits = HashMap::<String, u32>::new();
-        crumbs.iter().for_each(|crumb| {
-            *num_visits.entry(crumb.to_string()).or_default() += 1;
-        });
-        let node_visits = *num_visits.entry(node.to_string()).or_default();
-        let num_revisited = num_visits
-            .iter()
-            .filter(|(_, count)| **count > 1)
-            .filter(|(x, _)| x.chars().nth(0).unwrap().is_lowercase())
-            .count() as u32;
-        if node == start {
-            false
-        } else if node == end {
-            node_visits <= 0
-        } else if node.chars().nth(0).unwrap().is_uppercase() {
-            true
-        } else if num_revisited >= max_small_revisits && node_visits > 0 {
-            false
-        } else {
-            true
+    fn intern(&mut self, name: &str) -> usize {
+        if let Some(id) = self.ids.get(name) {
+            return *id;
+        }
+        let id = self.names.len();
+        assert!(id < MAX_CAVES, "more than {} caves", MAX_CAVES);
+        if is_small(name) {
+            self.small |= 1 << id;
         }
+        self.names.push(name.to_string());
+        self.ids.insert(name.to_string(), id);
+        self.connections.push(Vec::new());
+        id
     }
 
-    pub fn walk(self: &Self, start: &str, end: &str) -> Vec<Vec<String>> {
-        self.walk_internal(&vec![start.to_string()], start, end, 0)
+    /// Number of paths from `start` to `end`, counted without building any of them
+    pub fn count_paths(&self, start: &str, end: &str, small_caves: SmallCaves) -> usize {
+        match (self.ids.get(start), self.ids.get(end)) {
+            (Some(start), Some(end)) => {
+                let mut counter = PathCounter {
+                    caves: self,
+                    start: *start,
+                    end: *end,
+                    memo: HashMap::new(),
+                };
+                let visited = self.small & (1 << start);
+                counter.count(*start, visited, small_caves == SmallCaves::VisitOnce)
+            }
+            _ => 0,
+        }
     }
 
-    #[allow(dead_code)]
-    pub fn walk_leisurely(self: &Self, start: &str, end: &str) -> Vec<Vec<String>> {
-        self.walk_internal(&vec![start.to_string()], start, end, 1)
+    /// Lazily enumerates every path from `start` to `end` as the names of its caves
+    #[cfg(test)]
+    pub fn paths(&self, start: &str, end: &str, small_caves: SmallCaves) -> Paths<'_> {
+        let stack = match (self.ids.get(start), self.ids.get(end)) {
+            (Some(start), Some(_)) => vec![PathFrame {
+                cave: *start,
+                next: 0,
+                revisit: false,
+            }],
+            _ => Vec::new(),
+        };
+        Paths {
+            caves: self,
+            end: self.ids.get(end).copied().unwrap_or(usize::MAX),
+            stack,
+            visited: self.ids.get(start).map_or(0, |start| self.small & (1 << start)),
+            revisit_used: small_caves == SmallCaves::VisitOnce,
+        }
     }
+}
+
+/// Memoized path counts keyed on (cave, small caves visited, revisit used)
+struct PathCounter<'a> {
+    caves: &'a Caves,
+    start: usize,
+    end: usize,
+    memo: HashMap<(usize, u64, bool), usize>,
+}
 
-    pub fn walk_internal(
-        self: &Self,
-        crumbs: &Vec<String>,
-        start: &str,
-        end: &str,
-        max_small_revisits: u32,
-    ) -> Vec<Vec<String>> {
-        let last = crumbs.last().unwrap();
-        if last == end {
-            return vec![crumbs.clone()];
+impl PathCounter<'_> {
+    fn count(&mut self, cave: usize, visited: u64, revisit_used: bool) -> usize {
+        if cave == self.end {
+            return 1;
         }
-        match self.connections.get(last) {
-            Some(nexts) => nexts
-                .iter()
-                .filter(|next| Caves::can_visit(next, crumbs, start, end, max_small_revisits))
-                .flat_map(|next| {
-                    let mut new_crumbs = crumbs.clone();
-                    new_crumbs.push(next.to_string());
-                    self.walk_internal(&new_crumbs, start, end, max_small_revisits)
-                })
-                .collect(),
-            None => Vec::new(),
+        if let Some(count) = self.memo.get(&(cave, visited, revisit_used)) {
+            return *count;
         }
+        let caves = self.caves;
+        let mut count = 0;
+        for next in &caves.connections[cave] {
+            let next = *next;
+            if next == self.start {
+                continue;
+            }
+            if visited & (1 << next) == 0 {
+                let visited = visited | (caves.small & (1 << next));
+                count += self.count(next, visited, revisit_used);
+            } else if !revisit_used {
+                count += self.count(next, visited, true);
+            }
+        }
+        self.memo.insert((cave, visited, revisit_used), count);
+        count
     }
 }
 
-fn parse_caves(s: &str) -> Vec<(String, String)> {
-    parsing::parse_lines(s)
-        .iter()
-        .map(|s| {
-            let mut parts = s.split("-");
-            (
-                parts.next().unwrap().to_string(),
-                parts.next().unwrap().to_string(),
-            )
-        })
-        .collect()
+#[cfg(test)]
+struct PathFrame {
+    cave: usize,
+    /// index into the cave's connections of the next one to try
+    next: usize,
+    /// whether entering this cave used up the small cave revisit
+    revisit: bool,
+}
+
+/// Depth-first iterator over the paths through the caves
+#[cfg(test)]
+pub struct Paths<'a> {
+    caves: &'a Caves,
+    end: usize,
+    stack: Vec<PathFrame>,
+    visited: u64,
+    revisit_used: bool,
 }
 
+#[cfg(test)]
+impl Iterator for Paths<'_> {
+    type Item = Vec<String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let start = self.stack.first()?.cave;
+        loop {
+            let frame = self.stack.last_mut()?;
+            let cave = frame.cave;
+            if cave == self.end && frame.next == 0 {
+                frame.next = 1;
+                let path = self.stack.iter().map(|f| self.caves.names[f.cave].clone());
+                return Some(path.collect());
+            }
+            let next = match cave == self.end {
+                true => None,
+                false => self.caves.connections[cave].get(frame.next).copied(),
+            };
+            frame.next += 1;
+
+            match next {
+                Some(next) if next == start => {}
+                Some(next) if self.visited & (1 << next) == 0 => {
+                    self.visited |= self.caves.small & (1 << next);
+                    self.stack.push(PathFrame {
+                        cave: next,
+                        next: 0,
+                        revisit: false,
+                    });
+                }
+                Some(next) if !self.revisit_used => {
+                    self.revisit_used = true;
+                    self.stack.push(PathFrame {
+                        cave: next,
+                        next: 0,
+                        revisit: true,
+                    });
+                }
+                Some(_) => {}
+                None => {
+                    let frame = self.stack.pop().unwrap();
+                    if frame.revisit {
+                        self.revisit_used = false;
+                    } else {
+                        self.visited &= !(1 << frame.cave);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Small caves have lowercase names, and can't be visited as often as big ones
+fn is_small(name: &str) -> bool {
+    name.chars().next().is_some_and(|c| c.is_lowercase())
+}
+
+fn parse_caves(s: &str) -> Result<Vec<(String, String)>, ParseError> {
+    let mut names = HashSet::new();
+    parsing::parse_input(s, |line| {
+        let connection = line.token(0, "a connection like 'a-b'")?;
+        let caves = connection.split_n("-", 2, "a connection like 'a-b'")?;
+        line.end(1)?;
+        if let Some(cave) = caves.iter().find(|c| c.text.is_empty()) {
+            return Err(cave.error("a cave name"));
+        }
+        // paths could go back and forth between two big caves forever
+        if caves.iter().all(|c| !is_small(c.text)) {
+            return Err(connection.error("a connection to at least one small cave"));
+        }
+        for cave in &caves {
+            names.insert(cave.text.to_string());
+            if names.len() > MAX_CAVES {
+                return Err(cave.error(&format!("at most {} distinct caves", MAX_CAVES)));
+            }
+        }
+        Ok((caves[0].text.to_string(), caves[1].text.to_string()))
+    })
+}
+
+pub const INPUT: &str = r"
+re-js
+qx-CG
+start-js
+start-bj
+qx-ak
+js-bj
+ak-re
+CG-ak
+js-CG
+bj-re
+ak-lg
+lg-CG
+qx-re
+WP-ak
+WP-end
+re-lg
+end-ak
+WP-re
+bj-CG
+qx-start
+bj-WP
+JG-lg
+end-lg
+lg-iw
+    ";
+
 #[cfg(test)]
 mod test {
     use super::*;
 
-    #[test]
-    fn test_walk_small_cave() {
-        let caves = Caves::new(parse_caves(
-            r"
+    const SMALL: &str = r"
             start-A
             start-b
             A-c
@@ -154,15 +287,9 @@ mod test {
             b-d
             A-end
             b-end
-        ",
-        ));
-        assert_eq!(caves.walk("start", "end").len(), 10);
-    }
+    ";
 
-    #[test]
-    fn test_walk_medium_cave() {
-        let caves = Caves::new(parse_caves(
-            r"
+    const MEDIUM: &str = r"
             dc-end
             HN-start
             start-kj
@@ -173,15 +300,9 @@ mod test {
             kj-sa
             kj-HN
             kj-dc
-        ",
-        ));
-        assert_eq!(caves.walk("start", "end").len(), 19);
-    }
+    ";
 
-    #[test]
-    fn test_walk_large_cave() {
-        let caves = Caves::new(parse_caves(
-            r"
+    const LARGE: &str = r"
             fs-end
             he-DX
             fs-he
@@ -200,72 +321,74 @@ mod test {
             zg-he
             pj-fs
             start-RW
-        ",
-        ));
-        assert_eq!(caves.walk("start", "end").len(), 226);
+    ";
+
+    #[test]
+    fn test_walk_small_cave() {
+        let caves = Caves::new(parse_caves(SMALL).unwrap());
+        assert_eq!(caves.count_paths("start", "end", SmallCaves::VisitOnce), 10);
+        assert_eq!(caves.paths("start", "end", SmallCaves::VisitOnce).count(), 10);
+    }
+
+    #[test]
+    fn test_walk_medium_cave() {
+        let caves = Caves::new(parse_caves(MEDIUM).unwrap());
+        assert_eq!(caves.count_paths("start", "end", SmallCaves::VisitOnce), 19);
+        assert_eq!(caves.paths("start", "end", SmallCaves::VisitOnce).count(), 19);
+    }
+
+    #[test]
+    fn test_walk_large_cave() {
+        let caves = Caves::new(parse_caves(LARGE).unwrap());
+        assert_eq!(caves.count_paths("start", "end", SmallCaves::VisitOnce), 226);
+        assert_eq!(caves.paths("start", "end", SmallCaves::VisitOnce).count(), 226);
     }
 
     #[test]
     fn test_walk_leisurely_small_cave() {
-        let caves = Caves::new(parse_caves(
-            r"
-            start-A
-            start-b
-            A-c
-            A-b
-            b-d
-            A-end
-            b-end
-        ",
-        ));
-        let walks = caves.walk_leisurely("start", "end");
-        walks.iter().for_each(|walk| println!("{:?}", walk));
-        assert_eq!(caves.walk_leisurely("start", "end").len(), 36);
+        let caves = Caves::new(parse_caves(SMALL).unwrap());
+        assert_eq!(caves.count_paths("start", "end", SmallCaves::RevisitOne), 36);
+        assert_eq!(caves.paths("start", "end", SmallCaves::RevisitOne).count(), 36);
     }
 
     #[test]
     fn test_walk_leisurely_medium_cave() {
-        let caves = Caves::new(parse_caves(
-            r"
-            dc-end
-            HN-start
-            start-kj
-            dc-start
-            dc-HN
-            LN-dc
-            HN-end
-            kj-sa
-            kj-HN
-            kj-dc
-        ",
-        ));
-        assert_eq!(caves.walk_leisurely("start", "end").len(), 103);
+        let caves = Caves::new(parse_caves(MEDIUM).unwrap());
+        assert_eq!(caves.count_paths("start", "end", SmallCaves::RevisitOne), 103);
+        assert_eq!(caves.paths("start", "end", SmallCaves::RevisitOne).count(), 103);
     }
 
     #[test]
     fn test_walk_leisurely_large_cave() {
-        let caves = Caves::new(parse_caves(
-            r"
-            fs-end
-            he-DX
-            fs-he
-            start-DX
-            pj-DX
-            end-zg
-            zg-sl
-            zg-pj
-            pj-he
-            RW-he
-            fs-DX
-            pj-RW
-            zg-RW
-            start-pj
-            he-WI
-            zg-he
-            pj-fs
-            start-RW
-        ",
-        ));
-        assert_eq!(caves.walk_leisurely("start", "end").len(), 3509);
+        let caves = Caves::new(parse_caves(LARGE).unwrap());
+        assert_eq!(caves.count_paths("start", "end", SmallCaves::RevisitOne), 3509);
+        assert_eq!(caves.paths("start", "end", SmallCaves::RevisitOne).count(), 3509);
+    }
+
+    #[test]
+    fn test_paths() {
+        let caves = Caves::new(parse_caves(SMALL).unwrap());
+        let mut paths = caves
+            .paths("start", "end", SmallCaves::VisitOnce)
+            .map(|path| path.join(","))
+            .collect::<Vec<String>>();
+        paths.sort();
+        assert_eq!(paths[0], "start,A,b,A,c,A,end");
+        assert_eq!(paths[9], "start,b,end");
+        assert!(caves
+            .paths("start", "end", SmallCaves::RevisitOne)
+            .any(|path| path.join(",") == "start,A,b,A,b,A,c,A,end"));
+        assert_eq!(caves.paths("start", "nowhere", SmallCaves::VisitOnce).count(), 0);
+        assert_eq!(caves.count_paths("start", "nowhere", SmallCaves::VisitOnce), 0);
+    }
+
+    #[test]
+    fn test_parse_caves_errors() {
+        let many = (0..40).map(|i| format!("a{}-b{}\n", i, i)).collect::<String>();
+        let err = parse_caves(&many).unwrap_err();
+        assert_eq!((err.line, err.column, err.token.as_str()), (33, 1, "a32"));
+        assert!(parse_caves("a-").is_err());
+        let err = parse_caves("start-A\nA-B\nB-end").unwrap_err();
+        assert_eq!((err.line, err.column, err.token.as_str()), (2, 1, "A-B"));
     }
 }
@@ -1,18 +1,40 @@
-use std::cmp::max;
+use std::error::Error;
 use std::fmt;
 
-use crate::parsing;
+use crate::grid::{Grid, Point};
+use crate::parsing::{self, ParseError};
+use crate::solution::Solution;
 
-pub fn run() {
-    let (mut paper, folds) = get_paper();
-    paper.fold(&folds[0]);
-    println!(
-        "Day 13: After 1 fold there are {} marked",
+pub struct Day13 {
+    paper: Paper,
+    folds: Vec<Fold>,
+}
+
+impl Solution for Day13 {
+    type Answer1 = usize;
+    type Answer2 = String;
+
+    fn parse(input: &str) -> Result<Self, ParseError> {
+        let (paper, folds) = parse_paper(input)?;
+        Ok(Self { paper, folds })
+    }
+
+    fn part1(&self) -> usize {
+        let mut paper = self.paper.clone();
+        if let Some(fold) = self.folds.first() {
+            paper.fold(fold);
+        }
         paper.marked().len()
-    );
-    paper.fold_all(&folds[1..].to_vec());
-    println!("      : After all folds, paper looks like:");
-    println!("{}", paper);
+    }
+
+    /// The letters on the folded paper, or the paper itself for a human to read if that fails
+    fn part2(&self) -> String {
+        let mut paper = self.paper.clone();
+        paper.fold_all(&self.folds);
+        paper
+            .read_letters()
+            .unwrap_or_else(|err| format!("{}\n{}", err, paper))
+    }
 }
 
 #[derive(Clone)]
@@ -21,148 +43,176 @@ pub enum Fold {
     Y(u32),
 }
 
+#[derive(Clone)]
 pub struct Paper {
-    data: Vec<Vec<bool>>,
+    data: Grid<bool>,
 }
 
 impl Paper {
-    pub fn new(pts: &Vec<(u32, u32)>) -> Self {
-        let mut width = 0;
-        let mut height = 0;
-
-        pts.iter().for_each(|(x, y)| {
-            width = max(width, x+1);
-            height = max(height, y+1);
-        });
-
-        let mut data = vec![vec![false;width as usize]; height as usize];
-        pts.iter().for_each(|(x,y)| data[*y as usize][*x as usize] = true);
+    pub fn new(pts: &[(u32, u32)]) -> Self {
+        let width = pts.iter().map(|(x, _)| *x as usize + 1).max().unwrap_or(0);
+        let height = pts.iter().map(|(_, y)| *y as usize + 1).max().unwrap_or(0);
+        let mut data = Grid::new(width, height, false);
+        pts.iter().for_each(|(x, y)| data[(*x as usize, *y as usize)] = true);
         Self { data }
     }
 
+    /// Folds the paper over onto the part before the fold line, which is removed
     pub fn fold(&mut self, fold: &Fold) {
-        match fold {
+        let data = &self.data;
+        let marked = |point| data.get(point) == Some(&true);
+        let mirror = |pos: usize, fold_at: usize| (2 * fold_at).checked_sub(pos);
+        self.data = match fold {
             Fold::X(fold_x) => {
                 let fold_x = *fold_x as usize;
-                self.data.iter_mut().for_each(|row| {
-                    ((fold_x +1)..row.len()).for_each(|x| {
-                        let x = x as usize;
-                        let fold_offset = x - fold_x;
-                        let fold_to = fold_x - fold_offset;
-                        let fold_from = fold_x + fold_offset;
-                        row[fold_to] = row[fold_to] || row[fold_from];
-                    });
-                    row.truncate(fold_x);
-                });
-            },
+                Grid::from_fn(data.width().min(fold_x), data.height(), |(x, y)| {
+                    marked((x, y)) || mirror(x, fold_x).is_some_and(|x| marked((x, y)))
+                })
+            }
             Fold::Y(fold_y) => {
                 let fold_y = *fold_y as usize;
-                ((fold_y+1)..self.data.len()).for_each(|y| {
-                    let y = y as usize;
-                    let fold_offset = y - fold_y;
-                    let fold_to = fold_y - fold_offset;
-                    let fold_from = fold_y + fold_offset;
-                    (0..self.data[y].len()).for_each(|x| {
-                        let x = x as usize;
-                        self.data[fold_to][x] = self.data[fold_to][x] || self.data[fold_from][x];
-                    });
-                });
-                self.data.truncate(fold_y);
-            },
-        }
+                Grid::from_fn(data.width(), data.height().min(fold_y), |(x, y)| {
+                    marked((x, y)) || mirror(y, fold_y).is_some_and(|y| marked((x, y)))
+                })
+            }
+        };
     }
 
-    pub fn fold_all(&mut self, folds: &Vec<Fold>) {
+    pub fn fold_all(&mut self, folds: &[Fold]) {
         folds.iter().for_each(|fold| self.fold(fold));
     }
 
-    pub fn marked(&self) -> Vec<(u32, u32)> {
+    pub fn marked(&self) -> Vec<Point> {
         self.data
-        .iter()
-        .enumerate()
-        .flat_map(|(row, cols)| {
-           cols 
-                .iter()
-                .enumerate()
-                .map(move |(col, e)| (row, col, e))
-        })
-        .filter(|(_y, _x, marked)| **marked)
-        .map(|(y, x, _)| (x as u32, y as u32))
-        .collect()
+            .iter()
+            .filter(|(_, marked)| **marked)
+            .map(|(point, _)| point)
+            .collect()
     }
 }
 
 impl fmt::Display for Paper {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        self.data.iter().try_for_each(|row| {
-            row.iter()
-                .try_for_each(|col| write!(f, "{}", if *col { "#" } else { "." }))?;
-            write!(f, "\n")
-        })
+        write!(f, "{}", self.data.map(|marked| if *marked { '#' } else { '.' }))
     }
 }
 
-pub fn parse_paper(s: &str) -> (Paper, Vec<Fold>) {
-    let mut points = Vec::<(u32, u32)>::new();
-    let mut folds = Vec::<Fold>::new();
-    parsing::parse_input(s, |parts| {
-        if parts[0] == "fold" {
-            let mut cmd = parts[2].split("=");
-            let axis = cmd.next().unwrap();
-            let pos = cmd.next().unwrap().to_string().parse::<u32>().unwrap();
-            if axis == "x" {
-                folds.push(Fold::X(pos));
-            } else if axis == "y" {
-                folds.push(Fold::Y(pos));
+/// Height of the letters the folded paper spells out
+pub const GLYPH_HEIGHT: usize = 6;
+/// Width of each letter, which is followed by a blank column
+pub const GLYPH_WIDTH: usize = 4;
+
+/// The letters of the AoC font, with rows separated by '/'
+const FONT: [(char, &str); 17] = [
+    ('A', ".##./#..#/#..#/####/#..#/#..#"),
+    ('B', "###./#..#/###./#..#/#..#/###."),
+    ('C', ".##./#..#/#.../#.../#..#/.##."),
+    ('E', "####/#.../###./#.../#.../####"),
+    ('F', "####/#.../###./#.../#.../#..."),
+    ('G', ".##./#..#/#.../#.##/#..#/.###"),
+    ('H', "#..#/#..#/####/#..#/#..#/#..#"),
+    ('I', ".###/..#./..#./..#./..#./.###"),
+    ('J', "..##/...#/...#/...#/#..#/.##."),
+    ('K', "#..#/#.#./##../#.#./#.#./#..#"),
+    ('L', "#.../#.../#.../#.../#.../####"),
+    ('O', ".##./#..#/#..#/#..#/#..#/.##."),
+    ('P', "###./#..#/#..#/###./#.../#..."),
+    ('R', "###./#..#/#..#/###./#.#./#..#"),
+    ('S', ".###/#.../#.../.##./...#/###."),
+    ('U', "#..#/#..#/#..#/#..#/#..#/.##."),
+    ('Z', "####/...#/..#./.#../#.../####"),
+];
+
+/// Why the folded paper couldn't be read as letters
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum OcrError {
+    /// The paper isn't `GLYPH_HEIGHT` rows tall
+    Height(usize),
+    /// Each glyph which isn't a known letter, along with its 0-based position
+    Unrecognized(Vec<(usize, String)>),
+}
+
+impl fmt::Display for OcrError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            OcrError::Height(height) => {
+                write!(f, "paper is {} rows tall, expected {}", height, GLYPH_HEIGHT)
+            }
+            OcrError::Unrecognized(glyphs) => {
+                write!(f, "unrecognized glyphs:")?;
+                glyphs
+                    .iter()
+                    .try_for_each(|(position, glyph)| write!(f, " {} '{}'", position, glyph))
             }
-        } else {
-            let mut pt = parts[0].split(",");
-            points.push((
-                pt.next().unwrap().parse::<u32>().unwrap(),
-                pt.next().unwrap().parse::<u32>().unwrap(),
-            ));
         }
-    });
-
-    (Paper::new(&points), folds)
+    }
 }
 
-#[cfg(test)]
-mod test {
-    use super::*;
+impl Error for OcrError {}
 
-    #[test]
-    fn test_folds() {
-        let (mut paper, folds) = parse_paper(r"
-            6,10
-            0,14
-            9,10
-            0,3
-            10,4
-            4,11
-            6,0
-            6,12
-            4,1
-            0,13
-            10,12
-            3,4
-            3,0
-            8,4
-            1,10
-            2,14
-            8,10
-            9,0
+impl Paper {
+    /// The glyph in each 4-wide cell, with rows separated by '/'
+    fn glyphs(&self) -> Vec<String> {
+        let cell = GLYPH_WIDTH + 1;
+        (0..self.data.width().div_ceil(cell))
+            .map(|i| {
+                let rows = self.data.rows().map(|row| {
+                    (i * cell..i * cell + GLYPH_WIDTH)
+                        .map(|x| if row.get(x) == Some(&true) { '#' } else { '.' })
+                        .collect::<String>()
+                });
+                rows.collect::<Vec<String>>().join("/")
+            })
+            .collect()
+    }
 
-            fold along y=7
-            fold along x=5
-        ");
-        paper.fold_all(&folds);
-        assert_eq!(paper.marked().len(), 16);
+    /// Reads the marked dots as capital letters
+    pub fn read_letters(&self) -> Result<String, OcrError> {
+        if self.data.height() != GLYPH_HEIGHT {
+            return Err(OcrError::Height(self.data.height()));
+        }
+        let mut letters = String::new();
+        let mut unrecognized = Vec::new();
+        for (position, glyph) in self.glyphs().into_iter().enumerate() {
+            match FONT.iter().find(|(_, g)| *g == glyph) {
+                Some((letter, _)) => letters.push(*letter),
+                None => unrecognized.push((position, glyph)),
+            }
+        }
+        if unrecognized.is_empty() {
+            Ok(letters)
+        } else {
+            Err(OcrError::Unrecognized(unrecognized))
+        }
     }
 }
 
-fn get_paper() -> (Paper, Vec<Fold>) {
-    parse_paper(r"
+pub fn parse_paper(s: &str) -> Result<(Paper, Vec<Fold>), ParseError> {
+    let mut points = Vec::<(u32, u32)>::new();
+    let mut folds = Vec::<Fold>::new();
+    parsing::parse_input(s, |line| {
+        if line.tokens[0].text == "fold" {
+            let cmd = line
+                .token(2, "a fold like 'x=5'")?
+                .split_n("=", 2, "a fold like 'x=5'")?;
+            let pos = cmd[1].parse::<u32>("a fold position")?;
+            match cmd[0].text {
+                "x" => folds.push(Fold::X(pos)),
+                "y" => folds.push(Fold::Y(pos)),
+                _ => return Err(cmd[0].error("an axis of x or y")),
+            }
+            line.end(3)
+        } else {
+            let pt = line.token(0, "a point like 'x,y'")?.split_n(",", 2, "a point like 'x,y'")?;
+            points.push((pt[0].parse("an x coordinate")?, pt[1].parse("a y coordinate")?));
+            line.end(1)
+        }
+    })?;
+
+    Ok((Paper::new(&points), folds))
+}
+
+pub const INPUT: &str = r"
 323,511
 1240,588
 1210,140
@@ -908,6 +958,82 @@ fold along x=40
 fold along y=27
 fold along y=13
 fold along y=6
-    ")
-}
+    ";
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_folds() {
+        let (mut paper, folds) = parse_paper(r"
+            6,10
+            0,14
+            9,10
+            0,3
+            10,4
+            4,11
+            6,0
+            6,12
+            4,1
+            0,13
+            10,12
+            3,4
+            3,0
+            8,4
+            1,10
+            2,14
+            8,10
+            9,0
 
+            fold along y=7
+            fold along x=5
+        ").unwrap();
+        paper.fold_all(&folds);
+        assert_eq!(paper.marked().len(), 16);
+    }
+
+    fn paper_from_rows(rows: &[&str]) -> Paper {
+        let data = crate::grid::parse_chars(&rows.join("\n"), "a row of dots").unwrap();
+        Paper {
+            data: data.map(|c| *c == '#'),
+        }
+    }
+
+    #[test]
+    fn test_read_letters() {
+        let paper = paper_from_rows(&[
+            "#..#..###.####",
+            "#..#...#..#...",
+            "####...#..###.",
+            "#..#...#..#...",
+            "#..#...#..#...",
+            "#..#..###.####",
+        ]);
+        assert_eq!(paper.read_letters(), Ok("HIE".to_string()));
+
+        let paper = paper_from_rows(&[
+            "#.....##.",
+            "#....#..#",
+            "#....#..#",
+            ".........",
+            ".........",
+            "####.####",
+        ]);
+        let err = paper.read_letters().unwrap_err();
+        assert_eq!(
+            err,
+            OcrError::Unrecognized(vec![
+                (0, "#.../#.../#.../..../..../####".to_string()),
+                (1, ".##./#..#/#..#/..../..../####".to_string()),
+            ])
+        );
+        assert_eq!(
+            err.to_string(),
+            "unrecognized glyphs: 0 '#.../#.../#.../..../..../####' 1 '.##./#..#/#..#/..../..../####'"
+        );
+
+        let paper = paper_from_rows(&["#..#", "#..#"]);
+        assert_eq!(paper.read_letters(), Err(OcrError::Height(2)));
+    }
+}
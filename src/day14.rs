@@ -1,169 +1,250 @@
-use crate::parsing;
+use crate::parsing::{self, ParseError};
+use crate::solution::Solution;
 use std::collections::HashMap;
 
-pub fn run() {
-    let mut polymer = get_polymer();
-    {
-        let ((min_c, min_count),(max_c, max_count)) = polymer.calc_min_max(10);
-        println!(
-            "Day 14: After 10 steps most common is {} ({}), least is {} ({}), Result={}",
-            max_c, max_count, min_c, min_count, max_count - min_count
-        );
+pub struct Day14 {
+    polymer: Polymer,
+}
+
+impl Solution for Day14 {
+    type Answer1 = u64;
+    type Answer2 = u64;
+
+    fn parse(input: &str) -> Result<Self, ParseError> {
+        Ok(Self {
+            polymer: Polymer::new_from_string(input)?,
+        })
     }
-    {
-        let ((min_c, min_count),(max_c, max_count)) = polymer.calc_min_max(40);
-        println!(
-            "      : After 40 steps most common is {} ({}), least is {} ({}), Result={}",
-            max_c, max_count, min_c, min_count, max_count - min_count
-        );
+
+    fn part1(&self) -> u64 {
+        let ((_, min_count), (_, max_count)) =
+            self.polymer.calc_min_max(10).expect("counts overflow");
+        max_count - min_count
+    }
+
+    fn part2(&self) -> u64 {
+        let ((_, min_count), (_, max_count)) =
+            self.polymer.calc_min_max(40).expect("counts overflow");
+        max_count - min_count
     }
 }
 
-type Rules = HashMap<(char,char),char>;
-type FreqsCache = HashMap<(char, char, u32), HashMap<char, usize>>;
+type Rules = HashMap<(char, char), char>;
+
+/// Square matrix over pairs, where entry [to][from] counts the `to` pairs one `from` pair becomes
+type Matrix = Vec<Vec<u64>>;
+
+#[derive(Clone, Debug)]
 pub struct Polymer {
-    start: Vec<char>,
+    template: Vec<char>,
     rules: Rules,
-    freqs_cache: FreqsCache,
+    /// every element in the template or rules; a pair (a, b) is indexed as a * len + b
+    elements: Vec<char>,
 }
 
 impl Polymer {
-    pub fn new(start: &Vec<char>, rules: &Rules) -> Self {
+    pub fn new(template: &[char], rules: &Rules) -> Self {
+        let mut elements = template.to_vec();
+        rules
+            .iter()
+            .for_each(|((l, r), inserted)| elements.extend([*l, *r, *inserted]));
+        elements.sort_unstable();
+        elements.dedup();
         Self {
-            start: start.clone(),
+            template: template.to_vec(),
             rules: rules.clone(),
-            freqs_cache: HashMap::new(),
+            elements,
         }
     }
 
-    pub fn new_from_string(s: &str) -> Self {
+    pub fn new_from_string(s: &str) -> Result<Self, ParseError> {
         let mut start = Vec::<char>::new();
         let mut rules = HashMap::new();
-        parsing::parse_input(s, |parts| {
+        parsing::parse_input(s, |line| {
             if start.is_empty() {
-                start = parts[0].chars().collect();
+                start = line.token(0, "a polymer template")?.text.chars().collect();
+                line.end(1)?;
             } else {
-                let (left, right) = (parts[0], parts[2]);
-                rules.insert((left.chars().nth(0).unwrap(), left.chars().nth(1).unwrap()), right.chars().nth(0).unwrap());
+                let pair = line.token(0, "a pair like 'AB'")?;
+                let arrow = line.token(1, "'->'")?;
+                if arrow.text != "->" {
+                    return Err(arrow.error("'->'"));
+                }
+                let inserted = line.token(2, "an element to insert")?;
+                line.end(3)?;
+                let pair_chars = pair.text.chars().collect::<Vec<char>>();
+                if pair_chars.len() != 2 {
+                    return Err(pair.error("a pair like 'AB'"));
+                }
+                if inserted.text.chars().count() != 1 {
+                    return Err(inserted.error("a single element"));
+                }
+                rules.insert((pair_chars[0], pair_chars[1]), inserted.text.chars().next().unwrap());
             }
-        });
-        Self::new(&start, &rules)
+            Ok(())
+        })?;
+        if start.is_empty() {
+            return Err(parsing::end_of_input(s, "a polymer template"));
+        }
+        Ok(Self::new(&start, &rules))
     }
 
-    pub fn calc_freqs(&mut self, steps: u32) -> HashMap<char, usize> {
-        let mut freqs_cache = HashMap::new();
-        let freqs = Self::calc_freqs_internal(&self.rules, &mut freqs_cache, &self.start, steps);
-        self.freqs_cache.extend(freqs_cache);
-        freqs
+    fn element_index(&self, c: char) -> usize {
+        self.elements.binary_search(&c).unwrap()
     }
 
-    fn calc_freqs_internal(rules: &Rules, freqs_cache: &mut FreqsCache, seq: &Vec<char>, steps: u32) -> HashMap<char, usize> {
-        let mut freqs = HashMap::<char, usize>::new();
-        seq.iter().take(seq.len() - 1)
-            .zip(seq.iter().skip(1)).for_each(|(left, right)| {
-                Self::calc_freqs_between(rules, freqs_cache, &(*left, *right), steps).iter()
-                    .for_each(|(c, count)| *freqs.entry(*c).or_default() += count);
-                *freqs.entry(*right).or_default() -= 1; // will count right side later as next left
-        });
-        if let Some(last) = seq.last() {
-            *freqs.entry(*last).or_default() += 1;
-        }
-        freqs
+    fn pair_index(&self, left: char, right: char) -> usize {
+        self.element_index(left) * self.elements.len() + self.element_index(right)
+    }
+
+    fn num_pairs(&self) -> usize {
+        self.elements.len() * self.elements.len()
     }
 
-    fn calc_freqs_between(rules: &Rules, freqs_cache: &mut FreqsCache, sub: &(char, char), steps: u32) -> HashMap<char, usize> {
-        if let Some(freqs) = freqs_cache.get(&(sub.0, sub.1, steps)) {
-            return freqs.clone();
+    /// Count of each pair in the template
+    fn template_pair_counts(&self) -> Vec<u64> {
+        let mut counts = vec![0; self.num_pairs()];
+        self.template
+            .windows(2)
+            .for_each(|pair| counts[self.pair_index(pair[0], pair[1])] += 1);
+        counts
+    }
+
+    /// The pairs each pair turns into after one step
+    fn successors(&self, pair: usize) -> Vec<usize> {
+        let (left, right) = (
+            self.elements[pair / self.elements.len()],
+            self.elements[pair % self.elements.len()],
+        );
+        match self.rules.get(&(left, right)) {
+            Some(inserted) => vec![
+                self.pair_index(left, *inserted),
+                self.pair_index(*inserted, right),
+            ],
+            None => vec![pair],
         }
-        let mut freqs = HashMap::new();
-        *freqs.entry(sub.0).or_default() += 1;
-        *freqs.entry(sub.1).or_default() += 1;
-        if steps >= 1 {
-            if let Some(inserted) = rules.get(sub) {
-                *freqs.entry(*inserted).or_default() += 1;
-
-                if steps > 1 {
-                    Self::calc_freqs_between(rules, freqs_cache, &(sub.0, *inserted), steps - 1).iter()
-                        .for_each(|(c, count)| *freqs.entry(*c).or_default() += count);
-                    Self::calc_freqs_between(rules, freqs_cache, &(*inserted, sub.1), steps - 1).iter()
-                        .for_each(|(c, count)| *freqs.entry(*c).or_default() += count);
-                    *freqs.entry(sub.0).or_default() -= 1;
-                    *freqs.entry(*inserted).or_default() -= 2;
-                    *freqs.entry(sub.1).or_default() -= 1;
+    }
+
+    /// Count of each pair after stepping one step at a time, or None if the counts overflow;
+    /// with a modulus the counts are kept modulo it
+    #[cfg(test)]
+    pub fn pair_counts(&self, steps: u64, modulus: Option<u64>) -> Option<Vec<u64>> {
+        let successors = (0..self.num_pairs()).map(|p| self.successors(p)).collect::<Vec<_>>();
+        let mut counts = self.template_pair_counts();
+        for _ in 0..steps {
+            let mut next = vec![0; counts.len()];
+            for (pair, count) in counts.iter().enumerate() {
+                for to in &successors[pair] {
+                    next[*to] = add(next[*to], *count, modulus)?;
                 }
             }
+            counts = next;
         }
-        freqs_cache.insert((sub.0, sub.1, steps), freqs.clone());
-        freqs
+        Some(counts)
     }
 
-    pub fn calc_min_max(&mut self, steps: u32) -> ((char, usize), (char, usize)) {
-        let mut min_count = 0 as usize;
-        let mut min_c = ' ';
-        let mut max_count = 0 as usize;
-        let mut max_c = ' ';
-        self.calc_freqs(steps).iter()
-            .for_each(|(c, count)| {
-                if min_count <= 0 || count < &min_count { min_c = *c;
-                    min_count = *count;
-                }
-                if count > &max_count {
-                    max_c = *c;
-                    max_count = *count;
-                }
-            });
-        ((min_c, min_count), (max_c, max_count))
+    /// Count of each pair after raising the step matrix to the power of `steps`, which takes
+    /// logarithmic time; otherwise the same as `pair_counts`
+    pub fn pair_counts_pow(&self, steps: u64, modulus: Option<u64>) -> Option<Vec<u64>> {
+        let n = self.num_pairs();
+        let mut step = vec![vec![0; n]; n];
+        for (from, successors) in (0..n).map(|p| self.successors(p)).enumerate() {
+            for to in successors {
+                step[to][from] += 1;
+            }
+        }
+
+        let mut counts = self.template_pair_counts();
+        let mut steps = steps;
+        while steps > 0 {
+            if steps & 1 == 1 {
+                counts = mul_vec(&step, &counts, modulus)?;
+            }
+            steps >>= 1;
+            if steps > 0 {
+                step = mul(&step, &step, modulus)?;
+            }
+        }
+        Some(counts)
     }
-}
 
-#[cfg(test)]
-mod test {
-    use super::*;
+    /// Count of each element given the count of each pair
+    pub fn element_counts(
+        &self,
+        pair_counts: &[u64],
+        modulus: Option<u64>,
+    ) -> Option<HashMap<char, u64>> {
+        let mut counts = HashMap::new();
+        // each element is the left of a pair, except the last which never changes
+        if let Some(last) = self.template.last() {
+            counts.insert(*last, 1);
+        }
+        for (pair, count) in pair_counts.iter().enumerate() {
+            if *count > 0 {
+                let entry = counts.entry(self.elements[pair / self.elements.len()]).or_default();
+                *entry = add(*entry, *count, modulus)?;
+            }
+        }
+        Some(counts)
+    }
 
-    #[test]
-    fn test_polymer_freqs() {
-        let mut polymer = Polymer::new_from_string(r"
-            NNNCCB
-        ");
-        let ((min_c, min_count),(max_c, max_count)) = polymer.calc_min_max(0);
-        assert_eq!(min_c, 'B');
-        assert_eq!(min_count, 1);
-        assert_eq!(max_c, 'N');
-        assert_eq!(max_count, 3);
+    /// Exact count of each element after the given steps, or None if a count overflows
+    pub fn calc_freqs(&self, steps: u64) -> Option<HashMap<char, u64>> {
+        self.element_counts(&self.pair_counts_pow(steps, None)?, None)
     }
 
-    #[test]
-    fn test_polymer_calc_min_max() {
-        let mut polymer = Polymer::new_from_string(r"
-            NNCB
+    /// The least and most common elements after the given steps, or None if a count overflows
+    pub fn calc_min_max(&self, steps: u64) -> Option<((char, u64), (char, u64))> {
+        let freqs = self.calc_freqs(steps)?;
+        let mut freqs = freqs.into_iter().collect::<Vec<_>>();
+        freqs.sort_unstable();
+        let min = freqs.iter().min_by_key(|(_, count)| *count)?;
+        let max = freqs.iter().max_by_key(|(_, count)| *count)?;
+        Some((*min, *max))
+    }
+}
 
-            CH -> B
-            HH -> N
-            CB -> H
-            NH -> C
-            HB -> C
-            HC -> B
-            HN -> C
-            NN -> C
-            BH -> H
-            NC -> B
-            NB -> B
-            BN -> B
-            BB -> N
-            BC -> B
-            CC -> N
-            CN -> C
-        ");
-        let ((min_c, min_count),(max_c, max_count)) = polymer.calc_min_max(10);
-        assert_eq!(min_c, 'H');
-        assert_eq!(min_count, 161);
-        assert_eq!(max_c, 'B');
-        assert_eq!(max_count, 1749);
+fn add(a: u64, b: u64, modulus: Option<u64>) -> Option<u64> {
+    match modulus {
+        Some(m) => Some(((a as u128 + b as u128) % m as u128) as u64),
+        None => a.checked_add(b),
     }
 }
 
-fn get_polymer() -> Polymer {
-    Polymer::new_from_string(r"
+fn mul_add(acc: u64, a: u64, b: u64, modulus: Option<u64>) -> Option<u64> {
+    match modulus {
+        Some(m) => Some(((acc as u128 + a as u128 * b as u128) % m as u128) as u64),
+        None => acc.checked_add(a.checked_mul(b)?),
+    }
+}
+
+fn mul(a: &Matrix, b: &Matrix, modulus: Option<u64>) -> Option<Matrix> {
+    let n = a.len();
+    let mut product = vec![vec![0; n]; n];
+    for i in 0..n {
+        for k in 0..n {
+            if a[i][k] == 0 {
+                continue;
+            }
+            for j in 0..n {
+                product[i][j] = mul_add(product[i][j], a[i][k], b[k][j], modulus)?;
+            }
+        }
+    }
+    Some(product)
+}
+
+fn mul_vec(a: &Matrix, v: &[u64], modulus: Option<u64>) -> Option<Vec<u64>> {
+    a.iter()
+        .map(|row| {
+            row.iter()
+                .zip(v)
+                .try_fold(0, |acc, (x, y)| mul_add(acc, *x, *y, modulus))
+        })
+        .collect()
+}
+
+pub const INPUT: &str = r"
 CNBPHFBOPCSPKOFNHVKV
 
 CS -> S
@@ -266,7 +347,87 @@ SV -> N
 KF -> K
 CV -> N
 NO -> P
-    ")
-}
+    ";
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_polymer_freqs() {
+        let polymer = Polymer::new_from_string(r"
+            NNNCCB
+        ").unwrap();
+        let ((min_c, min_count),(max_c, max_count)) = polymer.calc_min_max(0).unwrap();
+        assert_eq!(min_c, 'B');
+        assert_eq!(min_count, 1);
+        assert_eq!(max_c, 'N');
+        assert_eq!(max_count, 3);
+    }
+
+    const EXAMPLE: &str = r"
+            NNCB
+
+            CH -> B
+            HH -> N
+            CB -> H
+            NH -> C
+            HB -> C
+            HC -> B
+            HN -> C
+            NN -> C
+            BH -> H
+            NC -> B
+            NB -> B
+            BN -> B
+            BB -> N
+            BC -> B
+            CC -> N
+            CN -> C
+    ";
 
+    #[test]
+    fn test_polymer_calc_min_max() {
+        let polymer = Polymer::new_from_string(EXAMPLE).unwrap();
+        let ((min_c, min_count),(max_c, max_count)) = polymer.calc_min_max(10).unwrap();
+        assert_eq!(min_c, 'H');
+        assert_eq!(min_count, 161);
+        assert_eq!(max_c, 'B');
+        assert_eq!(max_count, 1749);
+    }
+
+    #[test]
+    fn test_polymer_calc_min_max_40() {
+        let polymer = Polymer::new_from_string(EXAMPLE).unwrap();
+        let ((min_c, min_count), (max_c, max_count)) = polymer.calc_min_max(40).unwrap();
+        assert_eq!((min_c, min_count), ('H', 3849876073));
+        assert_eq!((max_c, max_count), ('B', 2192039569602));
+        assert_eq!(polymer.calc_freqs(100), None);
+    }
 
+    #[test]
+    fn test_pair_counts_pow() {
+        let polymer = Polymer::new_from_string(EXAMPLE).unwrap();
+        for steps in [0, 1, 4, 10, 40] {
+            assert_eq!(polymer.pair_counts_pow(steps, None), polymer.pair_counts(steps, None));
+        }
+        let counts = polymer.pair_counts(4, None).unwrap();
+        assert_eq!(counts.iter().sum::<u64>(), 48);
+        assert_eq!(polymer.element_counts(&counts, None).unwrap()[&'B'], 23);
+
+        let modulus = Some(1_000_000_007);
+        assert_eq!(polymer.pair_counts_pow(2000, modulus), polymer.pair_counts(2000, modulus));
+        let counts = polymer.pair_counts_pow(1_000_000, modulus).unwrap();
+        assert!(counts.iter().all(|count| *count < 1_000_000_007));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let err = Polymer::new_from_string("\n").unwrap_err();
+        assert_eq!((err.line, err.expected.as_str()), (1, "a polymer template"));
+        let err = Polymer::new_from_string("NNCB\n\nCH => B").unwrap_err();
+        assert_eq!((err.line, err.column, err.token.as_str()), (3, 4, "=>"));
+        let err = Polymer::new_from_string("NNCB\n\nCHH -> B").unwrap_err();
+        assert_eq!((err.column, err.token.as_str()), (1, "CHH"));
+    }
+}
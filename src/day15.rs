@@ -1,114 +1,234 @@
-use crate::parsing;
-use pathfinding::prelude::dijkstra;
-
-pub fn run() {
-    let cave = get_cave();
-    match find_shortest_path(&cave, &(0, 0), &(cave[0].len() - 1, cave.len() - 1)) {
-        Some((path, length)) => {
-            println!("Day 15: There are {} steps for a total length of {}", path.len(), length);
-        },
-        None => println!("Day 15: No paths found."),
-    }
-    
-    let expanded_cave = expand_cave(&cave, 5);
-    match find_shortest_path(&expanded_cave, &(0, 0), &(expanded_cave[0].len() - 1, expanded_cave.len() - 1)) {
-        Some((path, length)) => {
-            println!("      : (Expanded) There are {} steps for a total length of {}", path.len(), length);
-        },
-        None => println!("    : (Expanded) No paths found."),
+use crate::grid::{self, Grid, Point};
+use crate::parsing::{self, ParseError};
+use crate::solution::Solution;
+
+pub struct Day15 {
+    cave: Grid<u8>,
+}
+
+impl Solution for Day15 {
+    type Answer1 = u32;
+    type Answer2 = u32;
+
+    fn parse(input: &str) -> Result<Self, ParseError> {
+        Ok(Self {
+            cave: parse_cave(input)?,
+        })
+    }
+
+    fn part1(&self) -> u32 {
+        lowest_total_risk(&TiledCave::new(&self.cave, 1))
+    }
+
+    fn part2(&self) -> u32 {
+        lowest_total_risk(&TiledCave::new(&self.cave, 5))
     }
 }
 
-pub fn expand_cave(cave: &Vec<Vec<u8>>, factor: usize) -> Vec<Vec<u8>> {
-    let mut expanded = Vec::new();
-    for row in 0..factor*cave.len() {
-        let orig_row = row % cave.len(); 
-        expanded.push(vec![0; factor*cave[orig_row].len()]);
-        for col in 0..factor*cave[orig_row].len() {
-            let sector_val = cave[row % cave.len()][col % cave[orig_row].len()]; 
-            let mut new_val = sector_val + (col / cave.len()) as u8 + (row / cave[orig_row].len()) as u8;
-            while new_val >= 10 {
-                new_val = 1 + (new_val - 10)
-            }
-            expanded[row][col] = new_val;
-        }
+fn lowest_total_risk(cave: &TiledCave) -> u32 {
+    let (_, risk) = cave
+        .end()
+        .and_then(|end| find_shortest_path(cave, (0, 0), end, Engine::BucketQueue))
+        .expect("no path through the cave");
+    risk
+}
+
+/// The cave repeated `factor` times in each direction, with risk levels computed on demand.
+/// Each tile to the right or down is one riskier than the last, wrapping from 9 back to 1.
+#[derive(Clone, Copy)]
+pub struct TiledCave<'a> {
+    cave: &'a Grid<u8>,
+    factor: usize,
+}
+
+impl<'a> TiledCave<'a> {
+    pub fn new(cave: &'a Grid<u8>, factor: usize) -> Self {
+        Self { cave, factor }
+    }
+
+    pub fn width(&self) -> usize {
+        self.cave.width() * self.factor
+    }
+
+    pub fn height(&self) -> usize {
+        self.cave.height() * self.factor
+    }
+
+    /// The bottom right corner, or None if the cave is empty
+    pub fn end(&self) -> Option<Point> {
+        Some((self.width().checked_sub(1)?, self.height().checked_sub(1)?))
+    }
+
+    pub fn risk(&self, (x, y): Point) -> u32 {
+        let (tile_width, tile_height) = (self.cave.width(), self.cave.height());
+        let base = self.cave[(x % tile_width, y % tile_height)] as usize;
+        ((base - 1 + x / tile_width + y / tile_height) % 9 + 1) as u32
+    }
+
+    /// Each neighbor of a point along with the risk of entering it
+    pub fn neighbors(&self, point: Point) -> impl Iterator<Item = (Point, u32)> + '_ {
+        grid::neighbors4(point, self.width(), self.height()).map(|next| (next, self.risk(next)))
     }
-    expanded
+}
+
+/// Algorithm used to search for the least risky path. Ties between equally risky paths are
+/// broken the same way by every engine: each point is entered from the first neighbor, in
+/// row-major order, through which it can be reached at its lowest risk. The solution only
+/// uses the bucket queue, and the others are kept for tests to check it against.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Engine {
+    #[cfg(test)]
+    Dijkstra,
+    /// Guided by the Manhattan distance to the end, which never overestimates the risk
+    #[cfg(test)]
+    AStar,
+    /// Dijkstra with a circular bucket queue, since risks are only ever 1 to 9
+    BucketQueue,
 }
 
 pub fn find_shortest_path(
-    cave: &Vec<Vec<u8>>,
-    start: &(usize, usize),
-    end: &(usize, usize),
-) -> Option<(Vec<(usize, usize)>, u32)> {
-    if cave.is_empty() || cave[0].is_empty() {
+    cave: &TiledCave,
+    start: Point,
+    end: Point,
+    engine: Engine,
+) -> Option<(Vec<Point>, u32)> {
+    if cave.width() == 0 || cave.height() == 0 {
         return None;
     }
-    dijkstra(
-        start,
-        |&(x, y)| {
-            let mut neighbors = Vec::new();
-            if x >= 1 {
-                neighbors.push(((x - 1, y), cave[y][x - 1] as u32));
-            }
-            if y >= 1 {
-                neighbors.push(((x, y - 1), cave[y - 1][x] as u32));
-            }
-            if x + 1 < cave[0].len() {
-                neighbors.push(((x + 1, y), cave[y][x + 1] as u32));
-            }
-            if y + 1 < cave.len() {
-                neighbors.push(((x, y + 1), cave[y + 1][x] as u32));
-            }
-            neighbors
-        },
-        |node| node == end,
-    )
+    match engine {
+        #[cfg(test)]
+        Engine::Dijkstra => best_first_search(cave, start, end, |_| 0),
+        #[cfg(test)]
+        Engine::AStar => best_first_search(cave, start, end, |(x, y)| {
+            (x.abs_diff(end.0) + y.abs_diff(end.1)) as u32
+        }),
+        Engine::BucketQueue => bucket_queue_search(cave, start, end),
+    }
 }
 
-fn parse_cave(s: &str) -> Vec<Vec<u8>> {
-    let mut rows = Vec::new();
-    parsing::parse_input(s, |parts| {
-        rows.push(
-            parts[0]
-                .chars()
-                .map(|c| c.to_string().parse::<u8>().unwrap())
-                .collect(),
-        );
-    });
-    rows
+/// Lowest known risk of reaching each point, and the point it was reached from
+struct Visits {
+    risks: Grid<u32>,
+    parents: Grid<Option<Point>>,
 }
 
+impl Visits {
+    fn new(cave: &TiledCave, start: Point) -> Self {
+        let mut risks = Grid::new(cave.width(), cave.height(), u32::MAX);
+        risks[start] = 0;
+        Self {
+            risks,
+            parents: Grid::new(cave.width(), cave.height(), None),
+        }
+    }
+
+    fn risk(&self, point: Point) -> u32 {
+        self.risks[point]
+    }
+
+    /// Records reaching `to` from `from`, returning whether it's at a lower risk than before
+    fn reach(&mut self, from: Point, to: Point, risk: u32) -> bool {
+        let row_major = |(x, y): Point| (y, x);
+        let lower = risk < self.risks[to];
+        let earlier = self.parents[to].is_some_and(|p| row_major(from) < row_major(p));
+        if lower || (risk == self.risks[to] && earlier) {
+            self.risks[to] = risk;
+            self.parents[to] = Some(from);
+        }
+        lower
+    }
+
+    fn path(&self, end: Point) -> Vec<Point> {
+        let mut path = vec![end];
+        while let Some(parent) = self.parents[*path.last().unwrap()] {
+            path.push(parent);
+        }
+        path.reverse();
+        path
+    }
+}
+
+/// A* with the given heuristic, or Dijkstra when it's always 0; points with the same estimate
+/// are expanded least risky first, so the end is only reached once all its ties are settled
 #[cfg(test)]
-mod test {
-    use super::*;
+fn best_first_search(
+    cave: &TiledCave,
+    start: Point,
+    end: Point,
+    heuristic: impl Fn(Point) -> u32,
+) -> Option<(Vec<Point>, u32)> {
+    use std::cmp::Reverse;
+    use std::collections::BinaryHeap;
 
-    #[test]
-    fn test_cave_shortest_path() {
-        let cave = parse_cave(r"
-            1163751742
-            1381373672
-            2136511328
-            3694931569
-            7463417111
-            1319128137
-            1359912421
-            3125421639
-            1293138521
-            2311944581
-        ");
-        match find_shortest_path(&cave, &(0, 0), &(cave[0].len() - 1, cave.len() - 1)) {
-            Some((path, distance)) => {
-                assert_eq!(path.len(), 19);
-                assert_eq!(distance, 40);
-            },
-            None => panic!("No shortest path found!"),
+    let mut visits = Visits::new(cave, start);
+    let mut queue = BinaryHeap::new();
+    queue.push(Reverse((heuristic(start), 0, start)));
+    while let Some(Reverse((_, risk, point))) = queue.pop() {
+        if risk > visits.risk(point) {
+            continue; // already reached with a lower risk
+        }
+        if point == end {
+            return Some((visits.path(end), risk));
+        }
+        for (next, step) in cave.neighbors(point) {
+            if visits.reach(point, next, risk + step) {
+                queue.push(Reverse((risk + step + heuristic(next), risk + step, next)));
+            }
+        }
+    }
+    None
+}
+
+fn bucket_queue_search(
+    cave: &TiledCave,
+    start: Point,
+    end: Point,
+) -> Option<(Vec<Point>, u32)> {
+    const BUCKETS: usize = 10; // one more than the highest risk of a single step
+    let mut visits = Visits::new(cave, start);
+    // the queue only ever holds risks within 9 of the lowest, so it can wrap around
+    let mut buckets = vec![Vec::new(); BUCKETS];
+    let mut queued = 1;
+    buckets[0].push(start);
+
+    let mut risk = 0;
+    while queued > 0 {
+        while let Some(point) = buckets[risk as usize % BUCKETS].pop() {
+            queued -= 1;
+            if risk > visits.risk(point) {
+                continue;
+            }
+            if point == end {
+                return Some((visits.path(end), risk));
+            }
+            for (next, step) in cave.neighbors(point) {
+                if visits.reach(point, next, risk + step) {
+                    buckets[(risk + step) as usize % BUCKETS].push(next);
+                    queued += 1;
+                }
+            }
         }
+        risk += 1;
     }
+    None
 }
 
-fn get_cave() -> Vec<Vec<u8>> {
-    parse_cave(r"
+fn parse_cave(s: &str) -> Result<Grid<u8>, ParseError> {
+    let cave = grid::parse_rows(s, "a row of risk levels", |row| {
+        row.chars()
+            .map(|c| match c.parse("a risk level from 1 to 9")? {
+                0 => Err(c.error("a risk level from 1 to 9")),
+                risk => Ok(risk),
+            })
+            .collect()
+    })?;
+    match cave.height() {
+        0 => Err(parsing::end_of_input(s, "a row of risk levels")),
+        _ => Ok(cave),
+    }
+}
+
+pub const INPUT: &str = r"
 3576219475874583191916312133474175459337114195988185136398151631965391991813219974121211251194786128
 1713881999231519357799114192443351147195293575386923868711388519361669464234397975938889146414199688
 2151922113728581598379349983928489926261239862882888968998791929828316718921297117449631496888879522
@@ -209,5 +329,115 @@ fn get_cave() -> Vec<Vec<u8>> {
 6516291128536155113712481272992636373774429965994966353494115221979925592128929821611928492143929534
 3993993153713717844719492314391992638719111242291783837279215399219822611221331985991484863975594721
 5995891457939115884519762228128552168915218299831775259316392719113962218429351553267511523198149462
-    ")
-}
\ No newline at end of file
+    ";
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use pathfinding::prelude::dijkstra;
+    use std::time::{Duration, Instant};
+
+    const EXAMPLE: &str = r"
+            1163751742
+            1381373672
+            2136511328
+            3694931569
+            7463417111
+            1319128137
+            1359912421
+            3125421639
+            1293138521
+            2311944581
+    ";
+
+    const ENGINES: [Engine; 3] = [Engine::Dijkstra, Engine::AStar, Engine::BucketQueue];
+
+    /// Time taken by each engine to find the least risky path through a cave
+    fn benchmark(cave: &TiledCave, engines: &[Engine]) -> Vec<(Engine, Option<u32>, Duration)> {
+        engines
+            .iter()
+            .map(|engine| {
+                let now = Instant::now();
+                let end = cave.end();
+                let path = end.and_then(|end| find_shortest_path(cave, (0, 0), end, *engine));
+                let risk = path.map(|(_, risk)| risk);
+                (*engine, risk, now.elapsed())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_cave_shortest_path() {
+        let cave = parse_cave(EXAMPLE).unwrap();
+        let cave = TiledCave::new(&cave, 1);
+        match find_shortest_path(&cave, (0, 0), (9, 9), Engine::Dijkstra) {
+            Some((path, distance)) => {
+                assert_eq!(path.len(), 19);
+                assert_eq!(distance, 40);
+            },
+            None => panic!("No shortest path found!"),
+        }
+    }
+
+    #[test]
+    fn test_tiled_cave() {
+        let cave = parse_cave(EXAMPLE).unwrap();
+        let cave = TiledCave::new(&cave, 5);
+        assert_eq!((cave.width(), cave.height()), (50, 50));
+        let row = (0..50).map(|x| cave.risk((x, 0)).to_string()).collect::<String>();
+        assert_eq!(row, "11637517422274862853338597396444961841755517295286");
+        let row = (0..50).map(|x| cave.risk((x, 49)).to_string()).collect::<String>();
+        assert_eq!(row, "67554889357866599146897761125791887223681299833479");
+        assert_eq!(cave.end(), Some((49, 49)));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let err = parse_cave("\n").unwrap_err();
+        assert_eq!((err.line, err.expected.as_str()), (1, "a row of risk levels"));
+        let err = parse_cave("19\n10").unwrap_err();
+        assert_eq!((err.line, err.column, err.token.as_str()), (2, 2, "0"));
+        let err = parse_cave("19\n1x").unwrap_err();
+        assert_eq!((err.line, err.column, err.token.as_str()), (2, 2, "x"));
+
+        let empty = Grid::new(0, 0, 1);
+        let cave = TiledCave::new(&empty, 5);
+        assert_eq!(cave.end(), None);
+        assert!(benchmark(&cave, &ENGINES).iter().all(|(_, risk, _)| risk.is_none()));
+    }
+
+    #[test]
+    fn test_engines_agree() {
+        let cave = parse_cave(EXAMPLE).unwrap();
+        for (factor, expected) in [(1, 40), (5, 315)] {
+            let cave = TiledCave::new(&cave, factor);
+            let end = cave.end().unwrap();
+            let paths = ENGINES.map(|engine| {
+                find_shortest_path(&cave, (0, 0), end, engine).unwrap()
+            });
+            // the pathfinding crate's Dijkstra is an independent check on the risk, though it
+            // can break ties between equally risky paths differently
+            let reference = dijkstra(&(0, 0), |p| cave.neighbors(*p), |p| *p == end);
+            assert_eq!(reference.map(|(_, risk)| risk), Some(expected));
+            for (path, risk) in &paths {
+                assert_eq!(*risk, expected);
+                let path_risk = path.iter().skip(1).map(|point| cave.risk(*point));
+                assert_eq!(path_risk.sum::<u32>(), expected);
+            }
+            assert_eq!(paths[0], paths[1]);
+            assert_eq!(paths[0], paths[2]);
+        }
+    }
+
+    /// Run with `cargo test --release -- --ignored --nocapture bench_engines`
+    #[test]
+    #[ignore]
+    fn bench_engines() {
+        let cave = parse_cave(INPUT).unwrap();
+        for factor in [1, 5] {
+            for (engine, risk, elapsed) in benchmark(&TiledCave::new(&cave, factor), &ENGINES) {
+                println!("{}x {:?}: risk {:?} in {:?}", factor, engine, risk, elapsed);
+            }
+        }
+    }
+}
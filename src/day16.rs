@@ -0,0 +1,72 @@
+use crate::bits::{self, Packet};
+use crate::parsing::{self, ParseError};
+use crate::solution::Solution;
+
+pub struct Day16 {
+    packet: Packet,
+}
+
+impl Solution for Day16 {
+    type Answer1 = u64;
+    type Answer2 = u64;
+
+    fn parse(input: &str) -> Result<Self, ParseError> {
+        Ok(Self {
+            packet: parse_transmission(input)?,
+        })
+    }
+
+    fn part1(&self) -> u64 {
+        self.packet.version_sum()
+    }
+
+    fn part2(&self) -> u64 {
+        self.packet.eval()
+    }
+}
+
+fn parse_transmission(s: &str) -> Result<Packet, ParseError> {
+    let line = match parsing::lines(s).next() {
+        Some(line) => line,
+        None => return Err(parsing::end_of_input(s, "a hex transmission")),
+    };
+    let hex = line.token(0, "a hex transmission")?;
+    line.end(1)?;
+    bits::decode(hex.text).map_err(|err| {
+        // point at the hex digit holding the offending bit
+        let digit = hex.chars().nth(err.bit / 4);
+        ParseError {
+            line: hex.line,
+            column: digit.map_or(hex.column + hex.text.len(), |d| d.column),
+            token: digit.map_or(String::new(), |d| d.text.to_string()),
+            expected: format!("{} at bit {}", err.expected, err.bit),
+        }
+    })
+}
+
+// not embedded yet, so the input has to be in inputs/day16.txt
+pub const INPUT: &str = "";
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_transmission() {
+        let packet = parse_transmission("\n  A0016C880162017C3686B18A3D4780\n").unwrap();
+        assert_eq!(packet.version_sum(), 31);
+        assert_eq!(parse_transmission("9C0141080250320F1802104A08").unwrap().eval(), 1);
+
+        let err = parse_transmission("  D2FG28").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "line 1, column 6: expected a hex digit at bit 12, found 'G'"
+        );
+        let err = parse_transmission("D2F").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "line 1, column 4: expected a literal group at bit 12, found end of line"
+        );
+        assert!(parse_transmission("").is_err());
+    }
+}
@@ -0,0 +1,198 @@
+use std::iter;
+
+use crate::parsing::{self, ParseError, Token};
+use crate::solution::Solution;
+
+pub struct Day17 {
+    target: Target,
+}
+
+impl Solution for Day17 {
+    type Answer1 = i64;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Self, ParseError> {
+        Ok(Self {
+            target: parse_target(input)?,
+        })
+    }
+
+    fn part1(&self) -> i64 {
+        highest_apex(&self.target).expect("no velocity hits the target")
+    }
+
+    fn part2(&self) -> usize {
+        valid_velocities(&self.target).len()
+    }
+}
+
+/// The area the probe has to be in after some step, inclusive of its edges
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Target {
+    pub x_min: i64,
+    pub x_max: i64,
+    pub y_min: i64,
+    pub y_max: i64,
+}
+
+impl Target {
+    pub fn contains(&self, (x, y): (i64, i64)) -> bool {
+        (self.x_min..=self.x_max).contains(&x) && (self.y_min..=self.y_max).contains(&y)
+    }
+
+    /// Whether a probe at this position and velocity can never reach the target
+    fn missed(&self, (x, y): (i64, i64), (vx, vy): (i64, i64)) -> bool {
+        (y < self.y_min && vy < 0)
+            || (x > self.x_max && vx >= 0)
+            || (x < self.x_min && vx <= 0)
+    }
+}
+
+/// Position and velocity of a probe launched from (0, 0) after each step; drag slows x
+/// towards 0 and gravity pulls y down
+pub fn trajectory(velocity: (i64, i64)) -> impl Iterator<Item = ((i64, i64), (i64, i64))> {
+    iter::successors(Some(((0, 0), velocity)), |((x, y), (vx, vy))| {
+        Some(((x + vx, y + vy), (vx - vx.signum(), vy - 1)))
+    })
+    .skip(1)
+}
+
+/// The highest y a probe launched with this velocity reaches, if it hits the target
+pub fn hits(target: &Target, velocity: (i64, i64)) -> Option<i64> {
+    let mut apex = 0;
+    for (position, velocity) in trajectory(velocity) {
+        apex = apex.max(position.1);
+        if target.contains(position) {
+            return Some(apex);
+        }
+        if target.missed(position, velocity) {
+            return None;
+        }
+    }
+    None
+}
+
+/// Smallest speed which carries a probe at least `distance` before drag stops it
+fn min_speed(distance: i64) -> i64 {
+    // the probe travels the triangular number of its speed, so solve n(n + 1) / 2 >= distance
+    let mut speed = ((((8 * distance + 1) as f64).sqrt() - 1.0) / 2.0) as i64;
+    while speed * (speed + 1) / 2 < distance {
+        speed += 1;
+    }
+    speed
+}
+
+/// Initial velocities worth simulating, which contain every one that can hit the target
+pub fn velocity_bounds(target: &Target) -> ((i64, i64), (i64, i64)) {
+    // the first step mustn't overshoot, and the probe must be able to drift far enough
+    let vx = if target.x_min > 0 {
+        (min_speed(target.x_min), target.x_max)
+    } else if target.x_max < 0 {
+        (target.x_min, -min_speed(-target.x_max))
+    } else {
+        (target.x_min, target.x_max)
+    };
+    // a probe thrown upwards comes back down through y = 0 going one faster than it left,
+    // so it can't be thrown faster than the target is far from 0 either way
+    let vy = (
+        target.y_min.min(0),
+        target.y_min.abs().max(target.y_max.abs()),
+    );
+    (vx, vy)
+}
+
+/// Every initial velocity which leaves the probe in the target after some step
+pub fn valid_velocities(target: &Target) -> Vec<((i64, i64), i64)> {
+    let ((vx_min, vx_max), (vy_min, vy_max)) = velocity_bounds(target);
+    (vx_min..=vx_max)
+        .flat_map(|vx| (vy_min..=vy_max).map(move |vy| (vx, vy)))
+        .filter_map(|velocity| hits(target, velocity).map(|apex| (velocity, apex)))
+        .collect()
+}
+
+/// The highest apex of any probe which hits the target
+pub fn highest_apex(target: &Target) -> Option<i64> {
+    valid_velocities(target).iter().map(|(_, apex)| *apex).max()
+}
+
+fn parse_target(s: &str) -> Result<Target, ParseError> {
+    const EXPECTED: &str = "a target like 'target area: x=20..30, y=-10..-5'";
+    // the x range is followed by a comma
+    let range = |token: Token, axis: &str| {
+        let text = token.text.trim_end_matches(',');
+        Token { text, ..token }.range(axis)
+    };
+
+    let mut targets = parsing::parse_input(s, |line| {
+        if line.tokens.first().map(|t| t.text) != Some("target") {
+            return Err(line.error(EXPECTED));
+        }
+        let (x_min, x_max) = range(line.token(2, EXPECTED)?, "x=")?;
+        let (y_min, y_max) = range(line.token(3, EXPECTED)?, "y=")?;
+        line.end(4)?;
+        Ok(Target {
+            x_min,
+            x_max,
+            y_min,
+            y_max,
+        })
+    })?;
+    match targets.len() {
+        0 => Err(parsing::end_of_input(s, EXPECTED)),
+        _ => Ok(targets.remove(0)),
+    }
+}
+
+// not embedded yet, so the input has to be in inputs/day17.txt
+pub const INPUT: &str = "";
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXAMPLE: &str = "target area: x=20..30, y=-10..-5";
+
+    #[test]
+    fn test_trajectory() {
+        let positions = trajectory((7, 2)).take(8).map(|(p, _)| p).collect::<Vec<_>>();
+        assert_eq!(
+            positions,
+            vec![(7, 2), (13, 3), (18, 3), (22, 2), (25, 0), (27, -3), (28, -7), (28, -12)]
+        );
+
+        let target = parse_target(EXAMPLE).unwrap();
+        assert_eq!(hits(&target, (7, 2)), Some(3));
+        assert_eq!(hits(&target, (6, 9)), Some(45));
+        assert_eq!(hits(&target, (17, -4)), None);
+    }
+
+    #[test]
+    fn test_valid_velocities() {
+        let target = parse_target(EXAMPLE).unwrap();
+        assert_eq!(highest_apex(&target), Some(45));
+        assert_eq!(valid_velocities(&target).len(), 112);
+        assert_eq!(velocity_bounds(&target), ((6, 30), (-10, 10)));
+    }
+
+    #[test]
+    fn test_negative_x() {
+        let target = parse_target("target area: x=-30..-20, y=-10..-5").unwrap();
+        assert_eq!(highest_apex(&target), Some(45));
+        assert_eq!(valid_velocities(&target).len(), 112);
+        assert_eq!(hits(&target, (-6, 9)), Some(45));
+
+        let target = parse_target("target area: x=-2..3, y=-4..-1").unwrap();
+        assert!(valid_velocities(&target).iter().any(|((vx, _), _)| *vx == 0));
+        assert_eq!(highest_apex(&target), Some(6));
+    }
+
+    #[test]
+    fn test_parse_target_errors() {
+        let err = parse_target("target area: x=20..30, y=-10..x").unwrap_err();
+        assert_eq!((err.column, err.token.as_str()), (31, "x"));
+        let err = parse_target("target area: x=30..20, y=-10..-5").unwrap_err();
+        assert_eq!((err.column, err.token.as_str()), (16, "30..20"));
+        assert!(parse_target("target area: z=20..30, y=-10..-5").is_err());
+        assert!(parse_target("").is_err());
+    }
+}
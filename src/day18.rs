@@ -0,0 +1,397 @@
+use std::fmt;
+use std::iter::Sum;
+use std::ops::Add;
+
+use crate::parsing::{self, ParseError, Token};
+use crate::solution::Solution;
+
+pub struct Day18 {
+    numbers: Vec<SnailfishNumber>,
+}
+
+impl Solution for Day18 {
+    type Answer1 = u32;
+    type Answer2 = LargestPair;
+
+    fn parse(input: &str) -> Result<Self, ParseError> {
+        Ok(Self {
+            numbers: parse_numbers(input)?,
+        })
+    }
+
+    fn part1(&self) -> u32 {
+        let total: SnailfishNumber = self.numbers.iter().cloned().sum();
+        total.magnitude()
+    }
+
+    fn part2(&self) -> LargestPair {
+        LargestPair(largest_pair_magnitude(&self.numbers))
+    }
+}
+
+/// The largest magnitude of a sum of two numbers, if there are two to add
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LargestPair(pub Option<u32>);
+
+impl fmt::Display for LargestPair {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.0 {
+            Some(magnitude) => write!(f, "{}", magnitude),
+            None => write!(f, "no pair of numbers"),
+        }
+    }
+}
+
+/// Either a regular number or a pair of snailfish numbers
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SnailfishNumber {
+    Regular(u32),
+    Pair(Box<SnailfishNumber>, Box<SnailfishNumber>),
+}
+
+/// How deeply a pair has to be nested before it explodes
+const EXPLODE_DEPTH: usize = 4;
+/// Regular numbers at least this big split
+const SPLIT_AT: u32 = 10;
+
+impl SnailfishNumber {
+    pub fn pair(left: SnailfishNumber, right: SnailfishNumber) -> Self {
+        Self::Pair(Box::new(left), Box::new(right))
+    }
+
+    pub fn magnitude(&self) -> u32 {
+        match self {
+            Self::Regular(value) => *value,
+            Self::Pair(left, right) => 3 * left.magnitude() + 2 * right.magnitude(),
+        }
+    }
+
+    /// Explodes or splits once, returning false if the number is already reduced
+    pub fn reduce_step(&mut self) -> bool {
+        self.explode() || self.split()
+    }
+
+    pub fn reduce(&mut self) {
+        while self.reduce_step() {}
+    }
+
+    /// Explodes the leftmost pair of regular numbers nested inside four pairs, if there is one
+    pub fn explode(&mut self) -> bool {
+        self.explode_at(0).is_some()
+    }
+
+    /// Returns the values still to be added to the nearest regular numbers on either side
+    fn explode_at(&mut self, depth: usize) -> Option<(Option<u32>, Option<u32>)> {
+        let (left, right) = match self {
+            Self::Regular(_) => return None,
+            Self::Pair(left, right) => (left, right),
+        };
+        if depth >= EXPLODE_DEPTH {
+            if let (Self::Regular(l), Self::Regular(r)) = (left.as_ref(), right.as_ref()) {
+                let carry = (Some(*l), Some(*r));
+                *self = Self::Regular(0);
+                return Some(carry);
+            }
+        }
+        if let Some((carry_left, carry_right)) = left.explode_at(depth + 1) {
+            if let Some(value) = carry_right {
+                right.add_leftmost(value);
+            }
+            return Some((carry_left, None));
+        }
+        if let Some((carry_left, carry_right)) = right.explode_at(depth + 1) {
+            if let Some(value) = carry_left {
+                left.add_rightmost(value);
+            }
+            return Some((None, carry_right));
+        }
+        None
+    }
+
+    fn add_leftmost(&mut self, value: u32) {
+        match self {
+            Self::Regular(n) => *n += value,
+            Self::Pair(left, _) => left.add_leftmost(value),
+        }
+    }
+
+    fn add_rightmost(&mut self, value: u32) {
+        match self {
+            Self::Regular(n) => *n += value,
+            Self::Pair(_, right) => right.add_rightmost(value),
+        }
+    }
+
+    /// Splits the leftmost regular number of 10 or more into a pair, if there is one
+    pub fn split(&mut self) -> bool {
+        match self {
+            Self::Regular(n) if *n >= SPLIT_AT => {
+                *self = Self::pair(Self::Regular(*n / 2), Self::Regular(n.div_ceil(2)));
+                true
+            }
+            Self::Regular(_) => false,
+            Self::Pair(left, right) => left.split() || right.split(),
+        }
+    }
+}
+
+impl Add for SnailfishNumber {
+    type Output = SnailfishNumber;
+
+    /// Pairs up the two numbers and reduces the result
+    fn add(self, other: SnailfishNumber) -> SnailfishNumber {
+        let mut sum = Self::pair(self, other);
+        sum.reduce();
+        sum
+    }
+}
+
+impl Sum for SnailfishNumber {
+    /// Panics if there's nothing to add, as there's no snailfish zero
+    fn sum<I: Iterator<Item = SnailfishNumber>>(iter: I) -> Self {
+        iter.reduce(Add::add).expect("no snailfish numbers to add")
+    }
+}
+
+impl fmt::Display for SnailfishNumber {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Regular(value) => write!(f, "{}", value),
+            Self::Pair(left, right) => write!(f, "[{},{}]", left, right),
+        }
+    }
+}
+
+/// The largest magnitude of the sum of any two different numbers, added in either order
+pub fn largest_pair_magnitude(numbers: &[SnailfishNumber]) -> Option<u32> {
+    let indices = (0..numbers.len()).flat_map(|i| (0..numbers.len()).map(move |j| (i, j)));
+    indices
+        .filter(|(i, j)| i != j)
+        .map(|(i, j)| (numbers[i].clone() + numbers[j].clone()).magnitude())
+        .max()
+}
+
+/// Recursive descent over a token holding one snailfish number
+struct Parser<'a> {
+    token: Token<'a>,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    /// The next `len` bytes of the token
+    fn peek(&self, len: usize) -> Token<'a> {
+        Token {
+            text: &self.token.text[self.pos..self.pos + len],
+            column: self.token.column + self.pos,
+            ..self.token
+        }
+    }
+
+    /// The next character of the token, or an empty token at the end
+    fn peek_char(&self) -> Token<'a> {
+        let len = self.token.text[self.pos..].chars().next().map_or(0, char::len_utf8);
+        self.peek(len)
+    }
+
+    fn expect(&mut self, text: &str, expected: &str) -> Result<(), ParseError> {
+        let next = self.peek_char();
+        if next.text != text {
+            return Err(next.error(expected));
+        }
+        self.pos += text.len();
+        Ok(())
+    }
+
+    fn number(&mut self) -> Result<SnailfishNumber, ParseError> {
+        if self.peek_char().text == "[" {
+            self.pos += 1;
+            let left = self.number()?;
+            self.expect(",", "',' between the halves of a pair")?;
+            let right = self.number()?;
+            self.expect("]", "']' closing a pair")?;
+            return Ok(SnailfishNumber::pair(left, right));
+        }
+        let digits = self.token.text[self.pos..].bytes().take_while(u8::is_ascii_digit).count();
+        if digits == 0 {
+            return Err(self.peek_char().error("a regular number or '['"));
+        }
+        let value = self.peek(digits).parse("a regular number")?;
+        self.pos += digits;
+        Ok(SnailfishNumber::Regular(value))
+    }
+}
+
+fn parse_number(token: Token) -> Result<SnailfishNumber, ParseError> {
+    let mut parser = Parser { token, pos: 0 };
+    let number = parser.number()?;
+    let rest = parser.peek(token.text.len() - parser.pos);
+    if !rest.text.is_empty() {
+        return Err(rest.error("the end of the number"));
+    }
+    Ok(number)
+}
+
+fn parse_numbers(s: &str) -> Result<Vec<SnailfishNumber>, ParseError> {
+    let numbers = parsing::parse_input(s, |line| {
+        line.end(1)?;
+        parse_number(line.token(0, "a snailfish number")?)
+    })?;
+    match numbers.is_empty() {
+        true => Err(parsing::end_of_input(s, "a snailfish number")),
+        false => Ok(numbers),
+    }
+}
+
+// not embedded yet, so the input has to be in inputs/day18.txt
+pub const INPUT: &str = "";
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXAMPLE: &str = "
+[[[0,[5,8]],[[1,7],[9,6]]],[[4,[1,2]],[[1,4],2]]]
+[[[5,[2,8]],4],[5,[[9,9],0]]]
+[6,[[[6,2],[5,6]],[[7,6],[4,7]]]]
+[[[6,[0,7]],[0,9]],[4,[9,[9,0]]]]
+[[[7,[6,4]],[3,[1,3]]],[[[5,5],1],9]]
+[[6,[[7,3],[3,2]]],[[[3,8],[5,7]],4]]
+[[[[5,4],[7,7]],8],[[8,3],8]]
+[[9,3],[[9,9],[6,[4,9]]]]
+[[2,[[7,7],7]],[[5,8],[[9,3],[0,2]]]]
+[[[[5,2],5],[8,[3,7]]],[[5,[7,5]],[4,4]]]
+";
+
+    const LARGER_EXAMPLE: &str = "
+[[[0,[4,5]],[0,0]],[[[4,5],[2,6]],[9,5]]]
+[7,[[[3,7],[4,3]],[[6,3],[8,8]]]]
+[[2,[[0,8],[3,4]]],[[[6,7],1],[7,[1,6]]]]
+[[[[2,4],7],[6,[0,5]]],[[[6,8],[2,8]],[[2,1],[4,5]]]]
+[7,[5,[[3,8],[1,4]]]]
+[[2,[2,2]],[8,[8,1]]]
+[2,9]
+[1,[[[9,3],9],[[9,0],[0,7]]]]
+[[[5,[7,4]],7],1]
+[[[[4,2],2],6],[8,7]]
+";
+
+    fn number(s: &str) -> SnailfishNumber {
+        parse_numbers(s).unwrap().remove(0)
+    }
+
+    fn sum(s: &str) -> String {
+        parse_numbers(s).unwrap().into_iter().sum::<SnailfishNumber>().to_string()
+    }
+
+    #[test]
+    fn test_explode() {
+        let examples = [
+            ("[[[[[9,8],1],2],3],4]", "[[[[0,9],2],3],4]"),
+            ("[7,[6,[5,[4,[3,2]]]]]", "[7,[6,[5,[7,0]]]]"),
+            ("[[6,[5,[4,[3,2]]]],1]", "[[6,[5,[7,0]]],3]"),
+            ("[[3,[2,[1,[7,3]]]],[6,[5,[4,[3,2]]]]]", "[[3,[2,[8,0]]],[9,[5,[4,[3,2]]]]]"),
+            ("[[3,[2,[8,0]]],[9,[5,[4,[3,2]]]]]", "[[3,[2,[8,0]]],[9,[5,[7,0]]]]"),
+        ];
+        for (before, after) in examples {
+            let mut n = number(before);
+            assert!(n.explode(), "{}", before);
+            assert_eq!(n.to_string(), after);
+        }
+        assert!(!number("[[[[0,9],2],3],4]").explode());
+    }
+
+    #[test]
+    fn test_reduce_steps() {
+        let mut n = number("[[[[4,3],4],4],[7,[[8,4],9]]]") + number("[1,1]");
+        assert_eq!(n.to_string(), "[[[[0,7],4],[[7,8],[6,0]]],[8,1]]");
+
+        n = SnailfishNumber::pair(number("[[[[4,3],4],4],[7,[[8,4],9]]]"), number("[1,1]"));
+        assert_eq!(n.to_string(), "[[[[[4,3],4],4],[7,[[8,4],9]]],[1,1]]");
+        let steps = [
+            "[[[[0,7],4],[7,[[8,4],9]]],[1,1]]",
+            "[[[[0,7],4],[15,[0,13]]],[1,1]]",
+            "[[[[0,7],4],[[7,8],[0,13]]],[1,1]]",
+            "[[[[0,7],4],[[7,8],[0,[6,7]]]],[1,1]]",
+            "[[[[0,7],4],[[7,8],[6,0]]],[8,1]]",
+        ];
+        for step in steps {
+            assert!(n.reduce_step());
+            assert_eq!(n.to_string(), step);
+        }
+        assert!(!n.reduce_step());
+
+        let mut n = number("[15,[0,13]]");
+        assert!(n.split());
+        assert_eq!(n.to_string(), "[[7,8],[0,13]]");
+    }
+
+    #[test]
+    fn test_sum() {
+        assert_eq!(sum("[1,1]\n[2,2]\n[3,3]\n[4,4]"), "[[[[1,1],[2,2]],[3,3]],[4,4]]");
+        assert_eq!(sum("[1,1]\n[2,2]\n[3,3]\n[4,4]\n[5,5]"), "[[[[3,0],[5,3]],[4,4]],[5,5]]");
+        assert_eq!(
+            sum("[1,1]\n[2,2]\n[3,3]\n[4,4]\n[5,5]\n[6,6]"),
+            "[[[[5,0],[7,4]],[5,5]],[6,6]]"
+        );
+
+        let numbers = parse_numbers(LARGER_EXAMPLE).unwrap();
+        assert_eq!(
+            (numbers[0].clone() + numbers[1].clone()).to_string(),
+            "[[[[4,0],[5,4]],[[7,7],[6,0]]],[[8,[7,7]],[[7,9],[5,0]]]]"
+        );
+        assert_eq!(
+            sum(LARGER_EXAMPLE),
+            "[[[[8,7],[7,7]],[[8,6],[7,7]]],[[[0,7],[6,6]],[8,7]]]"
+        );
+        assert_eq!(
+            sum(EXAMPLE),
+            "[[[[6,6],[7,6]],[[7,7],[7,0]]],[[[7,7],[7,7]],[[7,8],[9,9]]]]"
+        );
+    }
+
+    #[test]
+    fn test_magnitude() {
+        let examples = [
+            ("[[1,2],[[3,4],5]]", 143),
+            ("[[[[0,7],4],[[7,8],[6,0]]],[8,1]]", 1384),
+            ("[[[[1,1],[2,2]],[3,3]],[4,4]]", 445),
+            ("[[[[3,0],[5,3]],[4,4]],[5,5]]", 791),
+            ("[[[[5,0],[7,4]],[5,5]],[6,6]]", 1137),
+            ("[[[[8,7],[7,7]],[[8,6],[7,7]]],[[[0,7],[6,6]],[8,7]]]", 3488),
+        ];
+        for (s, magnitude) in examples {
+            assert_eq!(number(s).magnitude(), magnitude, "{}", s);
+        }
+    }
+
+    #[test]
+    fn test_homework() {
+        let day = Day18::parse(EXAMPLE).unwrap();
+        assert_eq!(day.part1(), 4140);
+        assert_eq!(day.part2(), LargestPair(Some(3993)));
+        assert_eq!(largest_pair_magnitude(&day.numbers[..1]), None);
+        let one = Day18::parse("[1,2]").unwrap();
+        assert_eq!((one.part1(), one.part2().to_string()), (7, "no pair of numbers".into()));
+    }
+
+    #[test]
+    fn test_parse() {
+        for line in EXAMPLE.lines().chain(LARGER_EXAMPLE.lines()).filter(|l| !l.is_empty()) {
+            assert_eq!(number(line).to_string(), line);
+        }
+        assert_eq!(number("[10,[3,123]]").to_string(), "[10,[3,123]]");
+
+        let err = parse_numbers("[1,2]\n[[1,2],3").unwrap_err();
+        assert_eq!((err.line, err.column, err.token.as_str()), (2, 9, ""));
+        let err = parse_numbers("[[1;2],3]").unwrap_err();
+        assert_eq!((err.column, err.token.as_str()), (4, ";"));
+        let err = parse_numbers("[1,2]]").unwrap_err();
+        assert_eq!((err.column, err.token.as_str()), (6, "]"));
+        let err = parse_numbers("[1,[a,2]]").unwrap_err();
+        assert_eq!((err.column, err.token.as_str()), (5, "a"));
+        let err = parse_numbers("[1, 2]").unwrap_err();
+        assert_eq!((err.column, err.token.as_str()), (5, "2]"));
+        let err = parse_numbers("\n\n").unwrap_err();
+        assert_eq!((err.line, err.column, err.token.as_str()), (1, 1, ""));
+    }
+}
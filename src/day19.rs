@@ -0,0 +1,461 @@
+use std::cell::OnceCell;
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+use std::fmt;
+
+use crate::geometry::{self, Rotation, Vec3};
+use crate::parsing::{self, ParseError};
+use crate::solution::Solution;
+
+pub struct Day19 {
+    scanners: Vec<Scanner>,
+    /// Located by whichever part runs first, as it's the slow bit
+    map: OnceCell<Option<BeaconMap>>,
+}
+
+impl Solution for Day19 {
+    type Answer1 = Located<usize>;
+    type Answer2 = Located<i32>;
+
+    fn parse(input: &str) -> Result<Self, ParseError> {
+        Ok(Self {
+            scanners: parse_scanners(input)?,
+            map: OnceCell::new(),
+        })
+    }
+
+    fn part1(&self) -> Located<usize> {
+        Located(self.map().map(|map| map.beacons.len()))
+    }
+
+    fn part2(&self) -> Located<i32> {
+        Located(self.map().and_then(|map| map.largest_distance()))
+    }
+}
+
+impl Day19 {
+    fn map(&self) -> Option<&BeaconMap> {
+        self.map.get_or_init(|| BeaconMap::locate(&self.scanners)).as_ref()
+    }
+}
+
+/// An answer which needs every scanner located, if they could all be lined up
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Located<T>(pub Option<T>);
+
+impl<T: fmt::Display> fmt::Display for Located<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.0 {
+            Some(answer) => write!(f, "{}", answer),
+            None => write!(f, "some scanner doesn't overlap any other"),
+        }
+    }
+}
+
+/// How many beacons two scanners have to share before they're considered to overlap
+pub const MIN_OVERLAP: usize = 12;
+
+/// The beacons one scanner can see, relative to itself
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Scanner {
+    pub beacons: Vec<Vec3>,
+    /// The squared distance between every pair of beacons, which doesn't depend on where the
+    /// scanner is or which way it faces, sorted by distance
+    pairs: Vec<(i64, usize, usize)>,
+}
+
+impl Scanner {
+    pub fn new(beacons: Vec<Vec3>) -> Self {
+        let mut pairs = Vec::new();
+        for (i, a) in beacons.iter().enumerate() {
+            for (j, b) in beacons.iter().enumerate().skip(i + 1) {
+                pairs.push((a.distance_squared(*b), i, j));
+            }
+        }
+        pairs.sort_unstable();
+        Self { beacons, pairs }
+    }
+
+    /// Distances between beacons found in both scanners, repeated as often as both have them
+    fn shared_distances(&self, other: &Scanner) -> Vec<i64> {
+        let (mut a, mut b) = (self.pairs.iter().peekable(), other.pairs.iter().peekable());
+        let mut shared = Vec::new();
+        while let (Some(&&(da, _, _)), Some(&&(db, _, _))) = (a.peek(), b.peek()) {
+            if da <= db {
+                a.next();
+            }
+            if db <= da {
+                b.next();
+            }
+            if da == db {
+                shared.push(da);
+            }
+        }
+        shared
+    }
+
+    /// The beacons at either end of a pair with one of these distances
+    fn beacons_at(&self, distances: &HashSet<i64>) -> Vec<Vec3> {
+        let indices = self
+            .pairs
+            .iter()
+            .filter(|(d, _, _)| distances.contains(d))
+            .flat_map(|&(_, i, j)| [i, j])
+            .collect::<BTreeSet<_>>();
+        indices.into_iter().map(|i| self.beacons[i]).collect()
+    }
+}
+
+/// Where a scanner is and which way it faces, relative to another
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Placement {
+    pub rotation: Rotation,
+    pub position: Vec3,
+}
+
+impl Placement {
+    pub const ORIGIN: Placement = Placement {
+        rotation: Rotation::IDENTITY,
+        position: Vec3::new(0, 0, 0),
+    };
+
+    /// Converts a position seen by the placed scanner into one seen by the other
+    pub fn apply(&self, v: Vec3) -> Vec3 {
+        self.rotation * v + self.position
+    }
+
+    /// The placement of a scanner placed by `inner` relative to the one this places
+    pub fn compose(&self, inner: &Placement) -> Placement {
+        Placement {
+            rotation: self.rotation * inner.rotation,
+            position: self.apply(inner.position),
+        }
+    }
+}
+
+/// Finds where `scanner` is relative to `fixed`, if at least 12 of their beacons line up
+pub fn align(fixed: &Scanner, scanner: &Scanner) -> Option<Placement> {
+    // beacons both scanners see keep their distances to each other, so overlapping scanners
+    // share at least one distance for every pair of the overlapping beacons
+    let shared = fixed.shared_distances(scanner);
+    if shared.len() < MIN_OVERLAP * (MIN_OVERLAP - 1) / 2 {
+        return None;
+    }
+    let shared = shared.into_iter().collect::<HashSet<_>>();
+    let (targets, candidates) = (fixed.beacons_at(&shared), scanner.beacons_at(&shared));
+    if targets.len() < MIN_OVERLAP || candidates.len() < MIN_OVERLAP {
+        return None;
+    }
+
+    for &rotation in geometry::rotations() {
+        let mut offsets = HashMap::<Vec3, usize>::new();
+        for &candidate in candidates.iter() {
+            let turned = rotation * candidate;
+            for &target in targets.iter() {
+                let count = offsets.entry(target - turned).or_insert(0);
+                *count += 1;
+                if *count >= MIN_OVERLAP {
+                    let position = target - turned;
+                    return Some(Placement { rotation, position });
+                }
+            }
+        }
+    }
+    None
+}
+
+/// Every beacon and scanner, placed relative to the first scanner
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BeaconMap {
+    pub beacons: BTreeSet<Vec3>,
+    pub placements: Vec<Placement>,
+}
+
+impl BeaconMap {
+    /// Aligns each scanner with one already placed, or returns None if some never overlap
+    pub fn locate(scanners: &[Scanner]) -> Option<Self> {
+        let mut placements = vec![None; scanners.len()];
+        let mut queue = VecDeque::new();
+        if !scanners.is_empty() {
+            placements[0] = Some(Placement::ORIGIN);
+            queue.push_back(0);
+        }
+        while let Some(i) = queue.pop_front() {
+            let placed = placements[i].expect("queued scanners are placed");
+            for j in 0..scanners.len() {
+                if placements[j].is_some() {
+                    continue;
+                }
+                if let Some(relative) = align(&scanners[i], &scanners[j]) {
+                    placements[j] = Some(placed.compose(&relative));
+                    queue.push_back(j);
+                }
+            }
+        }
+
+        let placements = placements.into_iter().collect::<Option<Vec<_>>>()?;
+        let beacons = scanners
+            .iter()
+            .zip(placements.iter())
+            .flat_map(|(scanner, placement)| scanner.beacons.iter().map(|b| placement.apply(*b)))
+            .collect();
+        Some(Self {
+            beacons,
+            placements,
+        })
+    }
+
+    pub fn scanner_positions(&self) -> impl Iterator<Item = Vec3> + '_ {
+        self.placements.iter().map(|placement| placement.position)
+    }
+
+    /// The largest Manhattan distance between any two scanners
+    pub fn largest_distance(&self) -> Option<i32> {
+        self.scanner_positions()
+            .flat_map(|a| self.scanner_positions().map(move |b| a.manhattan_distance(b)))
+            .max()
+    }
+}
+
+fn parse_scanners(s: &str) -> Result<Vec<Scanner>, ParseError> {
+    const HEADER: &str = "a header like '--- scanner 0 ---'";
+    const BEACON: &str = "a beacon like 'x,y,z'";
+    let mut scanners = Vec::<Vec<Vec3>>::new();
+    parsing::parse_input(s, |line| {
+        if line.tokens[0].text == "---" {
+            let number = line.token(2, HEADER)?;
+            if number.parse::<usize>(HEADER)? != scanners.len() {
+                return Err(number.error(&format!("scanner {}", scanners.len())));
+            }
+            scanners.push(Vec::new());
+            return line.end(4);
+        }
+        let beacons = match scanners.last_mut() {
+            Some(beacons) => beacons,
+            None => return Err(line.error(HEADER)),
+        };
+        let coords = line.token(0, BEACON)?.split_n(",", 3, BEACON)?;
+        line.end(1)?;
+        beacons.push(Vec3::new(
+            coords[0].parse("an x coordinate")?,
+            coords[1].parse("a y coordinate")?,
+            coords[2].parse("a z coordinate")?,
+        ));
+        Ok(())
+    })?;
+    if scanners.is_empty() {
+        return Err(parsing::end_of_input(s, HEADER));
+    }
+    Ok(scanners.into_iter().map(Scanner::new).collect())
+}
+
+// not embedded yet, so the input has to be in inputs/day19.txt
+pub const INPUT: &str = "";
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXAMPLE: &str = "
+--- scanner 0 ---
+404,-588,-901
+528,-643,409
+-838,591,734
+390,-675,-793
+-537,-823,-458
+-485,-357,347
+-345,-311,381
+-661,-816,-575
+-876,649,763
+-618,-824,-621
+553,345,-567
+474,580,667
+-447,-329,318
+-584,868,-557
+544,-627,-890
+564,392,-477
+455,729,728
+-892,524,684
+-689,845,-530
+423,-701,434
+7,-33,-71
+630,319,-379
+443,580,662
+-789,900,-551
+459,-707,401
+
+--- scanner 1 ---
+686,422,578
+605,423,415
+515,917,-361
+-336,658,858
+95,138,22
+-476,619,847
+-340,-569,-846
+567,-361,727
+-460,603,-452
+669,-402,600
+729,430,532
+-500,-761,534
+-322,571,750
+-466,-666,-811
+-429,-592,574
+-355,545,-477
+703,-491,-529
+-328,-685,520
+413,935,-424
+-391,539,-444
+586,-435,557
+-364,-763,-893
+807,-499,-711
+755,-354,-619
+553,889,-390
+
+--- scanner 2 ---
+649,640,665
+682,-795,504
+-784,533,-524
+-644,584,-595
+-588,-843,648
+-30,6,44
+-674,560,763
+500,723,-460
+609,671,-379
+-555,-800,653
+-675,-892,-343
+697,-426,-610
+578,704,681
+493,664,-388
+-671,-858,530
+-667,343,800
+571,-461,-707
+-138,-166,112
+-889,563,-600
+646,-828,498
+640,759,510
+-630,509,768
+-681,-892,-333
+673,-379,-804
+-742,-814,-386
+577,-820,562
+
+--- scanner 3 ---
+-589,542,597
+605,-692,669
+-500,565,-823
+-660,373,557
+-458,-679,-417
+-488,449,543
+-626,468,-788
+338,-750,-386
+528,-832,-391
+562,-778,733
+-938,-730,414
+543,643,-506
+-524,371,-870
+407,773,750
+-104,29,83
+378,-903,-323
+-778,-728,485
+426,699,580
+-438,-605,-362
+-469,-447,-387
+509,732,623
+647,635,-688
+-868,-804,481
+614,-800,639
+595,780,-596
+
+--- scanner 4 ---
+727,592,562
+-293,-554,779
+441,611,-461
+-714,465,-776
+-743,427,-804
+-660,-479,-426
+832,-632,460
+927,-485,-438
+408,393,-506
+466,436,-512
+110,16,151
+-258,-428,682
+-393,719,612
+-211,-452,876
+808,-476,-593
+-575,615,604
+-485,667,467
+-680,325,-822
+-627,-443,-432
+872,-547,-609
+833,512,582
+807,604,487
+839,-516,451
+891,-625,532
+-652,-548,-490
+30,-46,-14
+";
+
+    #[test]
+    fn test_align() {
+        let scanners = parse_scanners(EXAMPLE).unwrap();
+        assert_eq!(scanners.iter().map(|s| s.beacons.len()).sum::<usize>(), 127);
+
+        let placement = align(&scanners[0], &scanners[1]).unwrap();
+        assert_eq!(placement.position, Vec3::new(68, -1246, -43));
+        let overlap = scanners[1]
+            .beacons
+            .iter()
+            .map(|b| placement.apply(*b))
+            .filter(|b| scanners[0].beacons.contains(b))
+            .collect::<BTreeSet<_>>();
+        assert_eq!(overlap.len(), 12);
+        assert!(overlap.contains(&Vec3::new(-618, -824, -621)));
+        assert!(overlap.contains(&Vec3::new(459, -707, 401)));
+
+        // scanners 0 and 2 don't overlap, so only get lined up through the others
+        assert_eq!(align(&scanners[0], &scanners[2]), None);
+        assert_eq!(align(&scanners[0], &scanners[0]), Some(Placement::ORIGIN));
+    }
+
+    #[test]
+    fn test_beacon_map() {
+        let day = Day19::parse(EXAMPLE).unwrap();
+        let map = day.map().unwrap();
+        assert_eq!(
+            map.scanner_positions().collect::<Vec<_>>(),
+            vec![
+                Vec3::new(0, 0, 0),
+                Vec3::new(68, -1246, -43),
+                Vec3::new(1105, -1205, 1229),
+                Vec3::new(-92, -2380, -20),
+                Vec3::new(-20, -1133, 1061),
+            ]
+        );
+        assert!(map.beacons.contains(&Vec3::new(-892, 524, 684)));
+        assert!(map.beacons.contains(&Vec3::new(1889, -1729, 1762)));
+        assert_eq!(day.part1(), Located(Some(79)));
+        assert_eq!(day.part2(), Located(Some(3621)));
+
+        let alone = EXAMPLE.split("--- scanner 2").next().unwrap().to_string()
+            + "--- scanner 2 ---\n0,0,0\n1,1,1";
+        let day = Day19::parse(&alone).unwrap();
+        assert_eq!(day.map(), None);
+        assert_eq!(day.part1().to_string(), "some scanner doesn't overlap any other");
+        assert_eq!(day.part2(), Located(None));
+        assert_eq!(BeaconMap::locate(&[]).and_then(|map| map.largest_distance()), None);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let err = parse_scanners("1,2,3").unwrap_err();
+        assert_eq!((err.line, err.column), (1, 1));
+        let err = parse_scanners("--- scanner 0 ---\n1,2").unwrap_err();
+        assert_eq!((err.line, err.token.as_str()), (2, "1,2"));
+        let err = parse_scanners("--- scanner 0 ---\n--- scanner 2 ---").unwrap_err();
+        assert_eq!((err.line, err.column, err.token.as_str()), (2, 13, "2"));
+        assert_eq!(err.expected, "scanner 1");
+        let err = parse_scanners("--- scanner 0 ---\n1,-2,z").unwrap_err();
+        assert_eq!((err.column, err.token.as_str()), (6, "z"));
+        let err = parse_scanners("\n").unwrap_err();
+        assert_eq!((err.line, err.expected.as_str()), (1, "a header like '--- scanner 0 ---'"));
+    }
+}
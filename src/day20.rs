@@ -0,0 +1,232 @@
+use std::fmt;
+
+use crate::grid::{self, Grid};
+use crate::parsing::{self, ParseError, Token};
+use crate::solution::Solution;
+
+pub struct Day20 {
+    enhancement: Enhancement,
+    image: InfiniteImage,
+}
+
+impl Solution for Day20 {
+    type Answer1 = LitCount;
+    type Answer2 = LitCount;
+
+    fn parse(input: &str) -> Result<Self, ParseError> {
+        let (enhancement, image) = parse_trench_map(input)?;
+        Ok(Self { enhancement, image })
+    }
+
+    fn part1(&self) -> LitCount {
+        LitCount(self.image.enhance_n(&self.enhancement, 2).lit_count())
+    }
+
+    fn part2(&self) -> LitCount {
+        LitCount(self.image.enhance_n(&self.enhancement, 50).lit_count())
+    }
+}
+
+/// How many pixels are lit, unless the background is lit and there are infinitely many
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LitCount(pub Option<usize>);
+
+impl fmt::Display for LitCount {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.0 {
+            Some(count) => write!(f, "{}", count),
+            None => write!(f, "infinitely many pixels are lit"),
+        }
+    }
+}
+
+/// Number of entries in the enhancement algorithm, one for each 3x3 block of pixels
+pub const ENHANCEMENT_SIZE: usize = 512;
+
+/// The image enhancement algorithm: whether a pixel is lit after enhancing, indexed by the
+/// 3x3 block around it read row by row as a binary number with lit pixels as 1
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Enhancement([bool; ENHANCEMENT_SIZE]);
+
+impl Enhancement {
+    pub fn new(lit: [bool; ENHANCEMENT_SIZE]) -> Self {
+        Self(lit)
+    }
+
+    pub fn lit(&self, index: usize) -> bool {
+        self.0[index]
+    }
+}
+
+/// An image which goes on forever: the pixels in a finite core, surrounded by pixels which
+/// are all lit or all dark
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InfiniteImage {
+    core: Grid<bool>,
+    background: bool,
+}
+
+impl InfiniteImage {
+    /// An image with only the pixels of `core` lit
+    pub fn new(core: Grid<bool>) -> Self {
+        Self {
+            core,
+            background: false,
+        }
+    }
+
+    /// Whether every pixel outside the core is lit
+    #[cfg(test)]
+    pub fn background(&self) -> bool {
+        self.background
+    }
+
+    /// The pixel at a position relative to the top left of the core
+    pub fn lit(&self, x: isize, y: isize) -> bool {
+        match (usize::try_from(x), usize::try_from(y)) {
+            (Ok(x), Ok(y)) => *self.core.get((x, y)).unwrap_or(&self.background),
+            _ => self.background,
+        }
+    }
+
+    /// Enhances every pixel at once. Pixels next to the core can change, so the core grows
+    /// by one on each side, and the background all changes to whatever its blocks map to.
+    pub fn enhance(&self, enhancement: &Enhancement) -> Self {
+        let (width, height) = (self.core.width() + 2, self.core.height() + 2);
+        let core = Grid::from_fn(width, height, |(x, y)| {
+            // (x, y) in the new core is (x - 1, y - 1) in the old one
+            let (x, y) = (x as isize - 1, y as isize - 1);
+            let index = (-1..=1)
+                .flat_map(|dy| (-1..=1).map(move |dx| (dx, dy)))
+                .fold(0, |index, (dx, dy)| index << 1 | self.lit(x + dx, y + dy) as usize);
+            enhancement.lit(index)
+        });
+        let background = match self.background {
+            false => enhancement.lit(0),
+            true => enhancement.lit(ENHANCEMENT_SIZE - 1),
+        };
+        Self { core, background }
+    }
+
+    pub fn enhance_n(&self, enhancement: &Enhancement, steps: usize) -> Self {
+        (0..steps).fold(self.clone(), |image, _| image.enhance(enhancement))
+    }
+
+    /// How many pixels are lit, or None if the background is lit and there are infinitely many
+    pub fn lit_count(&self) -> Option<usize> {
+        match self.background {
+            false => Some(self.core.iter().filter(|(_, lit)| **lit).count()),
+            true => None,
+        }
+    }
+}
+
+impl fmt::Display for InfiniteImage {
+    /// Just the core, as the background is the same all the way out
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.core.map(|lit| if *lit { '#' } else { '.' }))
+    }
+}
+
+fn parse_pixels(token: Token) -> Result<Vec<bool>, ParseError> {
+    token
+        .chars()
+        .map(|c| match c.text {
+            "#" => Ok(true),
+            "." => Ok(false),
+            _ => Err(c.error("a pixel of '#' or '.'")),
+        })
+        .collect()
+}
+
+fn parse_trench_map(s: &str) -> Result<(Enhancement, InfiniteImage), ParseError> {
+    const ALGORITHM: &str = "an enhancement algorithm of 512 pixels";
+    let mut lines = parsing::lines(s);
+    let line = lines.next().ok_or_else(|| parsing::end_of_input(s, ALGORITHM))?;
+    let token = line.token(0, ALGORITHM)?;
+    line.end(1)?;
+    let lit = parse_pixels(token)?;
+    let enhancement = Enhancement::new(lit.try_into().map_err(|_| token.error(ALGORITHM))?);
+
+    let core = grid::parse_row_lines(lines, "a row of pixels", parse_pixels)?;
+    Ok((enhancement, InfiniteImage::new(core)))
+}
+
+// not embedded yet, so the input has to be in inputs/day20.txt
+pub const INPUT: &str = "";
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXAMPLE: &str = "
+..#.#..#####.#.#.#.###.##.....###.##.#..###.####..#####..#....#..#..##..###..######.###...####..#..#####..##..#.#####...##.#.#..#.##..#.#......#.###.######.###.####...#.##.##..#..#..#####.....#.#....###..#.##......#.....#..#..#..##..#...##.######.####.####.#.#...#.......#..#.#.#...####.##.#......#..#...##.#.##..#...##.#.##..###.#......#.#.......#.#.#.####.###.##...#.....####.#..#..#.##.#....##..#.####....##...##..#...#......#.#.......#.......##..####..#...#.#.#...##..#.#..###..#####........#..####......#..#
+
+#..#.
+#....
+##..#
+..#..
+..###
+";
+
+    #[test]
+    fn test_enhance() {
+        let (enhancement, image) = parse_trench_map(EXAMPLE).unwrap();
+        assert_eq!(image.lit_count(), Some(10));
+        assert!(image.lit(0, 0) && !image.lit(-1, 0) && !image.lit(100, 100));
+
+        let once = image.enhance(&enhancement);
+        assert_eq!(
+            once.to_string(),
+            ".##.##.\n#..#.#.\n##.#..#\n####..#\n.#..##.\n..##..#\n...#.#.\n"
+        );
+        let twice = once.enhance(&enhancement);
+        assert_eq!(
+            twice.to_string(),
+            ".......#.\n.#..#.#..\n#.#...###\n#...##.#.\n#.....#.#\n\
+             .#.#####.\n..#.#####\n...##.##.\n....###..\n"
+        );
+        assert_eq!(twice.lit_count(), Some(35));
+        assert_eq!(image.enhance_n(&enhancement, 2), twice);
+
+        let day = Day20::parse(EXAMPLE).unwrap();
+        assert_eq!(day.part1(), LitCount(Some(35)));
+        assert_eq!(day.part2(), LitCount(Some(3351)));
+    }
+
+    #[test]
+    fn test_flipping_background() {
+        // lights every pixel which was dark and darkens every one which was lit, so the
+        // background flips every step
+        let invert = Enhancement::new(std::array::from_fn(|index| index & 0b000_010_000 == 0));
+        let (_, image) = parse_trench_map(EXAMPLE).unwrap();
+
+        let once = image.enhance(&invert);
+        assert!(once.background());
+        assert_eq!(once.lit_count(), None);
+        assert_eq!(once.to_string().lines().nth(1), Some("#.##.##"));
+
+        let twice = once.enhance(&invert);
+        assert!(!twice.background());
+        assert_eq!(twice.lit_count(), Some(10));
+        assert_eq!(twice.to_string().lines().nth(2), Some("..#..#..."));
+
+        // a lit background which stays lit
+        let algorithm = format!("#{}#", ".".repeat(ENHANCEMENT_SIZE - 2));
+        let day = Day20::parse(&format!("{}\n\n#.\n.#", algorithm)).unwrap();
+        assert_eq!(day.part1().to_string(), "infinitely many pixels are lit");
+        assert_eq!(day.part2(), LitCount(None));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let err = parse_trench_map("#.#\n\n#.").unwrap_err();
+        assert_eq!((err.line, err.column, err.token.as_str()), (1, 1, "#.#"));
+        let algorithm = EXAMPLE.lines().nth(1).unwrap();
+        let err = parse_trench_map(&format!("{}\n\n#..\n#.x", algorithm)).unwrap_err();
+        assert_eq!((err.line, err.column, err.token.as_str()), (4, 3, "x"));
+        let err = parse_trench_map(&format!("{}\n\n#..\n#.", algorithm)).unwrap_err();
+        assert_eq!(err.expected, "a row of pixels 3 wide");
+        assert!(parse_trench_map("").is_err());
+    }
+}
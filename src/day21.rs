@@ -0,0 +1,301 @@
+use std::collections::HashMap;
+
+use crate::parsing::{self, ParseError};
+use crate::solution::Solution;
+
+pub struct Day21 {
+    start: [u32; 2],
+}
+
+impl Solution for Day21 {
+    type Answer1 = u64;
+    type Answer2 = u64;
+
+    fn parse(input: &str) -> Result<Self, ParseError> {
+        Ok(Self {
+            start: parse_start(input)?,
+        })
+    }
+
+    fn part1(&self) -> u64 {
+        let game = play_deterministic(self.start, &Rules::PRACTICE);
+        game.losing_score() as u64 * game.rolls as u64
+    }
+
+    fn part2(&self) -> u64 {
+        let wins = count_universes(self.start, &Rules::DIRAC);
+        wins[0].max(wins[1])
+    }
+}
+
+/// The size of the circular track and the score which wins the game
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Rules {
+    pub board_size: u32,
+    pub winning_score: u32,
+}
+
+impl Rules {
+    /// The practice game with the deterministic die
+    pub const PRACTICE: Rules = Rules {
+        board_size: 10,
+        winning_score: 1000,
+    };
+    /// The game with the Dirac die, which splits the universe on every roll
+    pub const DIRAC: Rules = Rules {
+        board_size: 10,
+        winning_score: 21,
+    };
+
+    /// Where a pawn lands after moving forward from a space numbered from 1
+    pub fn advance(&self, position: u32, steps: u32) -> u32 {
+        (position - 1 + steps) % self.board_size + 1
+    }
+}
+
+/// A die which rolls 1, 2, 3 and so on, wrapping around to 1 after its highest side
+pub struct DeterministicDie {
+    sides: u32,
+    rolls: u32,
+}
+
+impl DeterministicDie {
+    pub fn new(sides: u32) -> Self {
+        Self { sides, rolls: 0 }
+    }
+
+    pub fn roll(&mut self) -> u32 {
+        self.rolls += 1;
+        (self.rolls - 1) % self.sides + 1
+    }
+}
+
+/// Sides of the die in the practice game
+pub const DETERMINISTIC_SIDES: u32 = 100;
+
+/// How a game with the deterministic die finished
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Outcome {
+    pub winner: usize,
+    pub scores: [u32; 2],
+    pub rolls: u32,
+}
+
+impl Outcome {
+    pub fn losing_score(&self) -> u32 {
+        self.scores[1 - self.winner]
+    }
+}
+
+/// Plays with the deterministic 100-sided die, each player rolling three times a turn
+pub fn play_deterministic(start: [u32; 2], rules: &Rules) -> Outcome {
+    let mut die = DeterministicDie::new(DETERMINISTIC_SIDES);
+    let mut positions = start;
+    let mut scores = [0; 2];
+    let mut player = 0;
+    loop {
+        let steps = die.roll() + die.roll() + die.roll();
+        positions[player] = rules.advance(positions[player], steps);
+        scores[player] += positions[player];
+        if scores[player] >= rules.winning_score {
+            return Outcome {
+                winner: player,
+                scores,
+                rolls: die.rolls,
+            };
+        }
+        player = 1 - player;
+    }
+}
+
+/// Each total of three rolls of the three-sided Dirac die, with how many of the 27 ways of
+/// rolling it give that total
+pub const ROLL_WEIGHTS: [(u32, u64); 7] = [(3, 1), (4, 3), (5, 6), (6, 7), (7, 6), (8, 3), (9, 1)];
+
+/// Everything that decides how a quantum game plays out from here
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+struct State {
+    positions: [u32; 2],
+    scores: [u32; 2],
+    player: usize,
+}
+
+/// Counts universes, remembering the counts from each state as they're found
+struct UniverseCounter<'a> {
+    rules: &'a Rules,
+    memo: HashMap<State, [u64; 2]>,
+}
+
+impl UniverseCounter<'_> {
+    /// How many universes each player wins in, starting from `state`
+    fn wins(&mut self, state: State) -> [u64; 2] {
+        if let Some(wins) = self.memo.get(&state) {
+            return *wins;
+        }
+        let mut wins = [0; 2];
+        let player = state.player;
+        for (steps, weight) in ROLL_WEIGHTS {
+            let mut next = state;
+            next.positions[player] = self.rules.advance(state.positions[player], steps);
+            next.scores[player] += next.positions[player];
+            if next.scores[player] >= self.rules.winning_score {
+                wins[player] += weight;
+                continue;
+            }
+            next.player = 1 - player;
+            let [first, second] = self.wins(next);
+            wins[0] += first * weight;
+            wins[1] += second * weight;
+        }
+        self.memo.insert(state, wins);
+        wins
+    }
+}
+
+/// How many universes each player wins in when playing with the Dirac die
+pub fn count_universes(start: [u32; 2], rules: &Rules) -> [u64; 2] {
+    let mut counter = UniverseCounter {
+        rules,
+        memo: HashMap::new(),
+    };
+    counter.wins(State {
+        positions: start,
+        scores: [0; 2],
+        player: 0,
+    })
+}
+
+fn parse_start(s: &str) -> Result<[u32; 2], ParseError> {
+    const EXPECTED: &str = "a start like 'Player 1 starting position: 4'";
+    let mut start = Vec::<u32>::new();
+    parsing::parse_input(s, |line| {
+        let player = line.token(1, EXPECTED)?;
+        if player.parse::<usize>(EXPECTED)? != start.len() + 1 {
+            return Err(player.error(&format!("player {}", start.len() + 1)));
+        }
+        let position = line.token(4, EXPECTED)?;
+        line.end(5)?;
+        match position.parse("a position")? {
+            0 => Err(position.error("a position from 1")),
+            position => {
+                start.push(position);
+                Ok(())
+            }
+        }
+    })?;
+    start
+        .try_into()
+        .map_err(|_| parsing::end_of_input(s, "two players"))
+}
+
+// not embedded yet, so the input has to be in inputs/day21.txt
+pub const INPUT: &str = "";
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXAMPLE: &str = "
+Player 1 starting position: 4
+Player 2 starting position: 8
+";
+
+    #[test]
+    fn test_deterministic() {
+        let start = parse_start(EXAMPLE).unwrap();
+        assert_eq!(start, [4, 8]);
+        assert_eq!(
+            play_deterministic(start, &Rules::PRACTICE),
+            Outcome {
+                winner: 0,
+                scores: [1000, 745],
+                rolls: 993,
+            }
+        );
+        assert_eq!(Day21::parse(EXAMPLE).unwrap().part1(), 739785);
+
+        let mut die = DeterministicDie::new(3);
+        assert_eq!((0..5).map(|_| die.roll()).collect::<Vec<_>>(), vec![1, 2, 3, 1, 2]);
+        assert_eq!(Rules::PRACTICE.advance(7, 5), 2);
+        assert_eq!(Rules::PRACTICE.advance(4, 6), 10);
+    }
+
+    /// The total of each of the 27 ways three rolls of the Dirac die can go
+    fn three_rolls() -> impl Iterator<Item = u32> {
+        (1..=3).flat_map(|a| (1..=3).flat_map(move |b| (1..=3).map(move |c| a + b + c)))
+    }
+
+    #[test]
+    fn test_roll_weights() {
+        let mut weights = HashMap::<u32, u64>::new();
+        for steps in three_rolls() {
+            *weights.entry(steps).or_insert(0) += 1;
+        }
+        let mut weights = weights.into_iter().collect::<Vec<_>>();
+        weights.sort();
+        assert_eq!(weights, ROLL_WEIGHTS);
+    }
+
+    #[test]
+    fn test_quantum() {
+        let wins = count_universes([4, 8], &Rules::DIRAC);
+        assert_eq!(wins, [444356092776315, 341960390180808]);
+        assert_eq!(Day21::parse(EXAMPLE).unwrap().part2(), 444356092776315);
+
+        // the first player always wins on their first turn when any score wins
+        let rules = Rules {
+            board_size: 10,
+            winning_score: 1,
+        };
+        assert_eq!(count_universes([4, 8], &rules), [27, 0]);
+    }
+
+    #[test]
+    fn test_quantum_variants() {
+        // count every universe one roll of three at a time, without remembering anything
+        fn brute_force(
+            positions: [u32; 2],
+            scores: [u32; 2],
+            player: usize,
+            rules: &Rules,
+        ) -> [u64; 2] {
+            let mut wins = [0; 2];
+            for steps in three_rolls() {
+                let (mut positions, mut scores) = (positions, scores);
+                positions[player] = rules.advance(positions[player], steps);
+                scores[player] += positions[player];
+                if scores[player] >= rules.winning_score {
+                    wins[player] += 1;
+                } else {
+                    let sub = brute_force(positions, scores, 1 - player, rules);
+                    wins = [wins[0] + sub[0], wins[1] + sub[1]];
+                }
+            }
+            wins
+        }
+        for (board_size, winning_score) in [(10, 5), (7, 4), (5, 3), (12, 4)] {
+            let rules = Rules {
+                board_size,
+                winning_score,
+            };
+            assert_eq!(
+                count_universes([1, 3], &rules),
+                brute_force([1, 3], [0, 0], 0, &rules),
+                "{:?}",
+                rules
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let err = parse_start("Player 1 starting position: 4").unwrap_err();
+        assert_eq!((err.line, err.expected.as_str()), (2, "two players"));
+        let err = parse_start("Player 2 starting position: 4").unwrap_err();
+        assert_eq!((err.column, err.token.as_str()), (8, "2"));
+        let err = parse_start("Player 1 starting position: 0").unwrap_err();
+        assert_eq!((err.column, err.token.as_str()), (29, "0"));
+        let err = parse_start("Player 1 starting position:").unwrap_err();
+        assert_eq!((err.column, err.token.as_str()), (28, ""));
+    }
+}
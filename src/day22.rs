@@ -0,0 +1,278 @@
+use std::collections::HashMap;
+
+use crate::geometry::Vec3;
+use crate::parsing::{self, ParseError};
+use crate::solution::Solution;
+
+pub struct Day22 {
+    steps: Vec<Step>,
+}
+
+impl Solution for Day22 {
+    type Answer1 = u64;
+    type Answer2 = u64;
+
+    fn parse(input: &str) -> Result<Self, ParseError> {
+        Ok(Self {
+            steps: parse_steps(input)?,
+        })
+    }
+
+    fn part1(&self) -> u64 {
+        reboot(&self.steps, Some(&Cuboid::INITIALIZATION))
+    }
+
+    fn part2(&self) -> u64 {
+        reboot(&self.steps, None)
+    }
+}
+
+/// The cubes from `min` to `max`, including both corners
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Cuboid {
+    pub min: Vec3,
+    pub max: Vec3,
+}
+
+impl Cuboid {
+    /// The region the reactor is initialized in, -50..50 along each axis
+    pub const INITIALIZATION: Cuboid = Cuboid {
+        min: Vec3::new(-50, -50, -50),
+        max: Vec3::new(50, 50, 50),
+    };
+
+    pub fn new(min: Vec3, max: Vec3) -> Self {
+        Self { min, max }
+    }
+
+    pub fn volume(&self) -> u64 {
+        let d = self.max - self.min;
+        (d.x as u64 + 1) * (d.y as u64 + 1) * (d.z as u64 + 1)
+    }
+
+    /// The cubes in both cuboids, if there are any
+    pub fn intersection(&self, other: &Cuboid) -> Option<Cuboid> {
+        let min = Vec3::new(
+            self.min.x.max(other.min.x),
+            self.min.y.max(other.min.y),
+            self.min.z.max(other.min.z),
+        );
+        let max = Vec3::new(
+            self.max.x.min(other.max.x),
+            self.max.y.min(other.max.y),
+            self.max.z.min(other.max.z),
+        );
+        let empty = min.x > max.x || min.y > max.y || min.z > max.z;
+        (!empty).then_some(Cuboid { min, max })
+    }
+}
+
+/// Turns every cube in a cuboid on or off
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Step {
+    pub on: bool,
+    pub cuboid: Cuboid,
+}
+
+/// The lit cubes as a sum of cuboids, each counted some number of times, possibly negative
+#[derive(Clone, Debug, Default)]
+pub struct Reactor {
+    counts: HashMap<Cuboid, i64>,
+}
+
+impl Reactor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancels out whatever was lit in the step's cuboid, then lights it all if it's on
+    pub fn apply(&mut self, step: &Step) {
+        let mut changes = HashMap::<Cuboid, i64>::new();
+        for (cuboid, count) in self.counts.iter() {
+            if let Some(overlap) = cuboid.intersection(&step.cuboid) {
+                *changes.entry(overlap).or_insert(0) -= count;
+            }
+        }
+        if step.on {
+            *changes.entry(step.cuboid).or_insert(0) += 1;
+        }
+        for (cuboid, change) in changes {
+            let count = self.counts.entry(cuboid).or_insert(0);
+            *count += change;
+            if *count == 0 {
+                self.counts.remove(&cuboid);
+            }
+        }
+    }
+
+    pub fn lit_count(&self) -> u64 {
+        let lit = self
+            .counts
+            .iter()
+            .map(|(cuboid, count)| cuboid.volume() as i64 * count)
+            .sum::<i64>();
+        lit as u64
+    }
+}
+
+/// How many cubes are lit after every step, only counting those in `region` if there is one
+pub fn reboot(steps: &[Step], region: Option<&Cuboid>) -> u64 {
+    let mut reactor = Reactor::new();
+    for step in steps {
+        let cuboid = match region {
+            Some(region) => match step.cuboid.intersection(region) {
+                Some(cuboid) => cuboid,
+                None => continue,
+            },
+            None => step.cuboid,
+        };
+        reactor.apply(&Step { cuboid, ..*step });
+    }
+    reactor.lit_count()
+}
+
+fn parse_steps(s: &str) -> Result<Vec<Step>, ParseError> {
+    const CUBOID: &str = "a cuboid like 'x=10..12,y=10..12,z=10..12'";
+    parsing::parse_input(s, |line| {
+        let state = line.token(0, "'on' or 'off'")?;
+        let on = match state.text {
+            "on" => true,
+            "off" => false,
+            _ => return Err(state.error("'on' or 'off'")),
+        };
+        let axes = line.token(1, CUBOID)?.split_n(",", 3, CUBOID)?;
+        line.end(2)?;
+        let (x, y, z) = (axes[0].range("x=")?, axes[1].range("y=")?, axes[2].range("z=")?);
+        let cuboid = Cuboid::new(Vec3::new(x.0, y.0, z.0), Vec3::new(x.1, y.1, z.1));
+        Ok(Step { on, cuboid })
+    })
+}
+
+// not embedded yet, so the input has to be in inputs/day22.txt
+pub const INPUT: &str = "";
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::collections::HashSet;
+
+    const SMALL_EXAMPLE: &str = "
+on x=10..12,y=10..12,z=10..12
+on x=11..13,y=11..13,z=11..13
+off x=9..11,y=9..11,z=9..11
+on x=10..10,y=10..10,z=10..10
+";
+
+    const EXAMPLE: &str = "
+on x=-20..26,y=-36..17,z=-47..7
+on x=-20..33,y=-21..23,z=-26..28
+on x=-22..28,y=-29..23,z=-38..16
+on x=-46..7,y=-6..46,z=-50..-1
+on x=-49..1,y=-3..46,z=-24..28
+on x=2..47,y=-22..22,z=-23..27
+on x=-27..23,y=-28..26,z=-21..29
+on x=-39..5,y=-6..47,z=-3..44
+on x=-30..21,y=-8..43,z=-13..34
+on x=-22..26,y=-27..20,z=-29..19
+off x=-48..-32,y=26..41,z=-47..-37
+on x=-12..35,y=6..50,z=-50..-2
+off x=-48..-32,y=-32..-16,z=-15..-5
+on x=-18..26,y=-33..15,z=-7..46
+off x=-40..-22,y=-38..-28,z=23..41
+on x=-16..35,y=-41..10,z=-47..6
+off x=-32..-23,y=11..30,z=-14..3
+on x=-49..-5,y=-3..45,z=-29..18
+off x=18..30,y=-20..-8,z=-3..13
+on x=-41..9,y=-7..43,z=-33..15
+on x=-54112..-39298,y=-85059..-49293,z=-27449..7877
+on x=967..23432,y=45373..81175,z=27513..53682
+";
+
+    /// Lights each cube one at a time, for checking small reboots against
+    fn voxels(steps: &[Step]) -> usize {
+        let mut lit = HashSet::new();
+        for step in steps {
+            let Cuboid { min, max } = step.cuboid;
+            for x in min.x..=max.x {
+                for y in min.y..=max.y {
+                    for z in min.z..=max.z {
+                        match step.on {
+                            true => lit.insert(Vec3::new(x, y, z)),
+                            false => lit.remove(&Vec3::new(x, y, z)),
+                        };
+                    }
+                }
+            }
+        }
+        lit.len()
+    }
+
+    #[test]
+    fn test_small_reboot() {
+        let steps = parse_steps(SMALL_EXAMPLE).unwrap();
+        let mut reactor = Reactor::new();
+        let counts = steps
+            .iter()
+            .map(|step| {
+                reactor.apply(step);
+                reactor.lit_count()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(counts, vec![27, 46, 38, 39]);
+        assert_eq!(voxels(&steps), 39);
+    }
+
+    #[test]
+    fn test_initialization_region() {
+        let day = Day22::parse(EXAMPLE).unwrap();
+        assert_eq!(day.part1(), 590784);
+
+        let inside = day
+            .steps
+            .iter()
+            .filter_map(|step| {
+                let cuboid = step.cuboid.intersection(&Cuboid::INITIALIZATION)?;
+                Some(Step { cuboid, ..*step })
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(inside.len(), 20);
+        assert_eq!(voxels(&inside), 590784);
+    }
+
+    #[test]
+    fn test_full_reboot() {
+        let steps = parse_steps(
+            "on x=-100000..100000,y=-100000..100000,z=-100000..100000
+             off x=-99999..99999,y=-99999..99999,z=-99999..99999
+             on x=0..0,y=0..0,z=0..0",
+        )
+        .unwrap();
+        // a hollow shell two hundred thousand cubes across, and the cube in its middle
+        assert_eq!(reboot(&steps, None), 200001u64.pow(3) - 199999u64.pow(3) + 1);
+        assert_eq!(reboot(&steps, Some(&Cuboid::INITIALIZATION)), 1);
+    }
+
+    #[test]
+    fn test_cuboid() {
+        let a = Cuboid::new(Vec3::new(10, 10, 10), Vec3::new(12, 12, 12));
+        let b = Cuboid::new(Vec3::new(12, 11, 9), Vec3::new(13, 13, 13));
+        assert_eq!(a.volume(), 27);
+        assert_eq!(
+            a.intersection(&b),
+            Some(Cuboid::new(Vec3::new(12, 11, 10), Vec3::new(12, 12, 12)))
+        );
+        assert_eq!(a.intersection(&Cuboid::new(Vec3::new(13, 0, 0), Vec3::new(20, 20, 20))), None);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let err = parse_steps("of x=1..2,y=1..2,z=1..2").unwrap_err();
+        assert_eq!((err.column, err.token.as_str()), (1, "of"));
+        let err = parse_steps("on x=1..2,y=1..2").unwrap_err();
+        assert_eq!((err.column, err.token.as_str()), (4, "x=1..2,y=1..2"));
+        let err = parse_steps("on x=1..2,z=1..2,y=1..2").unwrap_err();
+        assert_eq!((err.column, err.token.as_str()), (11, "z=1..2"));
+        let err = parse_steps("on x=1..2,y=3..-2,z=1..2").unwrap_err();
+        assert_eq!((err.column, err.token.as_str()), (13, "3..-2"));
+    }
+}
@@ -0,0 +1,26 @@
+use crate::parsing::ParseError;
+use crate::solution::{Skeleton, Solution, Unsolved};
+
+/// Not solved yet, so it answers whatever the shared `Skeleton` does
+pub struct Day25(Skeleton);
+
+impl Solution for Day25 {
+    type Answer1 = Unsolved;
+    type Answer2 = Unsolved;
+    const IMPLEMENTED: bool = false;
+
+    fn parse(input: &str) -> Result<Self, ParseError> {
+        Skeleton::parse(input).map(Self)
+    }
+
+    fn part1(&self) -> Unsolved {
+        self.0.part1()
+    }
+
+    fn part2(&self) -> Unsolved {
+        self.0.part2()
+    }
+}
+
+// not embedded yet, so the input has to be in inputs/day25.txt
+pub const INPUT: &str = "";
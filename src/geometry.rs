@@ -0,0 +1,170 @@
+//! Points and rotations in three dimensions
+
+use std::ops::{Add, Mul, Neg, Sub};
+use std::sync::OnceLock;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Vec3 {
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+}
+
+impl Vec3 {
+    pub const fn new(x: i32, y: i32, z: i32) -> Self {
+        Self { x, y, z }
+    }
+
+    fn coords(&self) -> [i32; 3] {
+        [self.x, self.y, self.z]
+    }
+
+    pub fn manhattan_distance(&self, other: Vec3) -> i32 {
+        let d = *self - other;
+        d.x.abs() + d.y.abs() + d.z.abs()
+    }
+
+    /// The square of the straight line distance, which stays exact in integers
+    pub fn distance_squared(&self, other: Vec3) -> i64 {
+        let d = *self - other;
+        d.coords().iter().map(|&c| c as i64 * c as i64).sum()
+    }
+}
+
+impl Add for Vec3 {
+    type Output = Vec3;
+
+    fn add(self, other: Vec3) -> Vec3 {
+        Vec3::new(self.x + other.x, self.y + other.y, self.z + other.z)
+    }
+}
+
+impl Sub for Vec3 {
+    type Output = Vec3;
+
+    fn sub(self, other: Vec3) -> Vec3 {
+        Vec3::new(self.x - other.x, self.y - other.y, self.z - other.z)
+    }
+}
+
+impl Neg for Vec3 {
+    type Output = Vec3;
+
+    fn neg(self) -> Vec3 {
+        Vec3::new(-self.x, -self.y, -self.z)
+    }
+}
+
+/// A rotation by multiples of 90 degrees about the axes, as a matrix of rows
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Rotation([[i32; 3]; 3]);
+
+impl Rotation {
+    pub const IDENTITY: Rotation = Rotation([[1, 0, 0], [0, 1, 0], [0, 0, 1]]);
+
+    fn determinant(&self) -> i32 {
+        let [a, b, c] = self.0;
+        a[0] * (b[1] * c[2] - b[2] * c[1]) - a[1] * (b[0] * c[2] - b[2] * c[0])
+            + a[2] * (b[0] * c[1] - b[1] * c[0])
+    }
+}
+
+impl Mul<Vec3> for Rotation {
+    type Output = Vec3;
+
+    fn mul(self, v: Vec3) -> Vec3 {
+        let [x, y, z] = self.0.map(|row| row.iter().zip(v.coords()).map(|(r, c)| r * c).sum());
+        Vec3::new(x, y, z)
+    }
+}
+
+impl Mul for Rotation {
+    type Output = Rotation;
+
+    /// The rotation which applies `other` first and then `self`
+    fn mul(self, other: Rotation) -> Rotation {
+        Rotation(std::array::from_fn(|i| {
+            std::array::from_fn(|j| (0..3).map(|k| self.0[i][k] * other.0[k][j]).sum())
+        }))
+    }
+}
+
+/// The 24 ways to turn something to face along an axis, starting with the identity
+pub fn rotations() -> &'static [Rotation] {
+    static ROTATIONS: OnceLock<Vec<Rotation>> = OnceLock::new();
+    ROTATIONS.get_or_init(|| {
+        // every matrix with a single 1 or -1 in each row and column is a rotation or a mirroring
+        const PERMUTATIONS: [[usize; 3]; 6] =
+            [[0, 1, 2], [0, 2, 1], [1, 0, 2], [1, 2, 0], [2, 0, 1], [2, 1, 0]];
+        let signed = PERMUTATIONS.iter().flat_map(|columns| {
+            (0..8).map(move |signs| {
+                Rotation(std::array::from_fn(|row| {
+                    let sign = if signs & (1 << row) == 0 { 1 } else { -1 };
+                    std::array::from_fn(|column| (column == columns[row]) as i32 * sign)
+                }))
+            })
+        });
+        signed.filter(|rotation| rotation.determinant() == 1).collect()
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_vec3() {
+        let (a, b) = (Vec3::new(1105, -1205, 1229), Vec3::new(-92, -2380, -20));
+        assert_eq!(a.manhattan_distance(b), 3621);
+        assert_eq!(a - b + b, a);
+        assert_eq!(-a + a, Vec3::default());
+        assert_eq!(Vec3::new(1, 2, 3).distance_squared(Vec3::new(4, 6, 3)), 25);
+    }
+
+    #[test]
+    fn test_rotations() {
+        let all = rotations();
+        assert_eq!(all.len(), 24);
+        assert_eq!(all[0], Rotation::IDENTITY);
+        assert_eq!(all.iter().collect::<HashSet<_>>().len(), 24);
+        // closed under composition, so each is some turn of the others
+        for a in all {
+            for b in all {
+                assert!(all.contains(&(*a * *b)));
+            }
+        }
+
+        let v = Vec3::new(1, 2, 3);
+        let images = all.iter().map(|r| *r * v).collect::<HashSet<_>>();
+        assert_eq!(images.len(), 24);
+        assert!(images.contains(&Vec3::new(-2, 1, 3)));
+        assert!(!images.contains(&Vec3::new(-1, 2, 3)));
+    }
+
+    #[test]
+    fn test_example_orientations() {
+        let beacons = |s: &str| -> Vec<Vec3> {
+            s.split_whitespace()
+                .map(|b| {
+                    let c = b.split(',').map(|c| c.parse().unwrap()).collect::<Vec<i32>>();
+                    Vec3::new(c[0], c[1], c[2])
+                })
+                .collect()
+        };
+        let scanner = beacons("-1,-1,1 -2,-2,2 -3,-3,3 -2,-3,1 5,6,-4 8,0,7");
+        let orientations = [
+            "1,-1,1 2,-2,2 3,-3,3 2,-1,3 -5,4,-6 -8,-7,0",
+            "-1,-1,-1 -2,-2,-2 -3,-3,-3 -1,-3,-2 4,6,5 -7,0,8",
+            "1,1,-1 2,2,-2 3,3,-3 1,3,-2 -4,-6,5 7,0,8",
+            "1,1,1 2,2,2 3,3,3 3,1,2 -6,-4,-5 0,7,-8",
+        ];
+        for orientation in orientations {
+            let expected = beacons(orientation);
+            let turned = rotations()
+                .iter()
+                .find(|r| scanner.iter().map(|b| **r * *b).eq(expected.iter().copied()));
+            assert!(turned.is_some(), "{}", orientation);
+        }
+    }
+}
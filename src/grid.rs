@@ -0,0 +1,269 @@
+use std::fmt;
+use std::ops::{Index, IndexMut};
+
+use crate::parsing::{self, Line, ParseError, Token};
+
+/// An (x, y) position, where x is the column and y the row
+pub type Point = (usize, usize);
+
+/// A rectangular grid of cells stored row by row
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    pub fn new(width: usize, height: usize, fill: T) -> Self
+    where
+        T: Clone,
+    {
+        Self {
+            width,
+            height,
+            cells: vec![fill; width * height],
+        }
+    }
+
+    pub fn from_fn(width: usize, height: usize, mut f: impl FnMut(Point) -> T) -> Self {
+        let cells = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(&mut f)
+            .collect();
+        Self {
+            width,
+            height,
+            cells,
+        }
+    }
+
+    /// Panics if the rows aren't all the same length
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Self {
+        let width = rows.first().map_or(0, |row| row.len());
+        assert!(rows.iter().all(|row| row.len() == width), "rows of different lengths");
+        Self {
+            width,
+            height: rows.len(),
+            cells: rows.into_iter().flatten().collect(),
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn contains(&self, (x, y): Point) -> bool {
+        x < self.width && y < self.height
+    }
+
+    pub fn get(&self, point: Point) -> Option<&T> {
+        self.contains(point).then(|| &self.cells[point.1 * self.width + point.0])
+    }
+
+    pub fn get_mut(&mut self, point: Point) -> Option<&mut T> {
+        match self.contains(point) {
+            true => Some(&mut self.cells[point.1 * self.width + point.0]),
+            false => None,
+        }
+    }
+
+    /// Every point in the grid, row by row
+    pub fn points(&self) -> impl Iterator<Item = Point> {
+        let width = self.width;
+        (0..self.height).flat_map(move |y| (0..width).map(move |x| (x, y)))
+    }
+
+    /// Every cell along with its point, row by row
+    pub fn iter(&self) -> impl Iterator<Item = (Point, &T)> {
+        self.points().zip(self.cells.iter())
+    }
+
+    /// The points up, down, left and right of a point which are inside the grid
+    pub fn neighbors4(&self, point: Point) -> impl Iterator<Item = Point> {
+        neighbors4(point, self.width, self.height)
+    }
+
+    /// The points around a point, including diagonally, which are inside the grid
+    pub fn neighbors8(&self, point: Point) -> impl Iterator<Item = Point> {
+        neighbors8(point, self.width, self.height)
+    }
+
+    pub fn row(&self, y: usize) -> &[T] {
+        &self.cells[y * self.width..(y + 1) * self.width]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        (0..self.height).map(|y| self.row(y))
+    }
+
+    #[cfg(test)]
+    pub fn column(&self, x: usize) -> impl Iterator<Item = &T> {
+        self.cells.iter().skip(x).step_by(self.width.max(1)).take(self.height)
+    }
+
+    #[cfg(test)]
+    pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+        (0..self.width).map(|x| self.column(x))
+    }
+
+    /// A copy of the grid with its rows and columns swapped
+    #[cfg(test)]
+    pub fn transpose(&self) -> Self
+    where
+        T: Clone,
+    {
+        Self::from_fn(self.height, self.width, |(x, y)| self[(y, x)].clone())
+    }
+
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid {
+            width: self.width,
+            height: self.height,
+            cells: self.cells.iter().map(f).collect(),
+        }
+    }
+}
+
+impl<T> Index<Point> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, point: Point) -> &T {
+        self.get(point)
+            .unwrap_or_else(|| panic!("{:?} is outside the grid", point))
+    }
+}
+
+impl<T> IndexMut<Point> for Grid<T> {
+    fn index_mut(&mut self, point: Point) -> &mut T {
+        self.get_mut(point)
+            .unwrap_or_else(|| panic!("{:?} is outside the grid", point))
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    /// Each row on its own line, with no space between cells
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.rows().try_for_each(|row| {
+            row.iter().try_for_each(|cell| write!(f, "{}", cell))?;
+            writeln!(f)
+        })
+    }
+}
+
+/// The points up, down, left and right of a point within a width by height area
+pub fn neighbors4(point: Point, width: usize, height: usize) -> impl Iterator<Item = Point> {
+    within([(-1, 0), (0, -1), (1, 0), (0, 1)], point, width, height)
+}
+
+/// The points around a point, including diagonally, within a width by height area
+pub fn neighbors8(point: Point, width: usize, height: usize) -> impl Iterator<Item = Point> {
+    const OFFSETS: [(isize, isize); 8] =
+        [(-1, -1), (0, -1), (1, -1), (-1, 0), (1, 0), (-1, 1), (0, 1), (1, 1)];
+    within(OFFSETS, point, width, height)
+}
+
+fn within<const N: usize>(
+    offsets: [(isize, isize); N],
+    (x, y): Point,
+    width: usize,
+    height: usize,
+) -> impl Iterator<Item = Point> {
+    offsets.into_iter().filter_map(move |(dx, dy)| {
+        let (x, y) = (x.checked_add_signed(dx)?, y.checked_add_signed(dy)?);
+        (x < width && y < height).then_some((x, y))
+    })
+}
+
+/// Parses a map with one row per line, where every row is `expected` and has the same width
+pub fn parse_rows<T, F>(s: &str, expected: &str, f: F) -> Result<Grid<T>, ParseError>
+where
+    F: FnMut(Token) -> Result<Vec<T>, ParseError>,
+{
+    parse_row_lines(parsing::lines(s), expected, f)
+}
+
+/// Like `parse_rows`, for a map which follows something else in the input
+pub fn parse_row_lines<'a, T, F>(
+    lines: impl Iterator<Item = Line<'a>>,
+    expected: &str,
+    mut f: F,
+) -> Result<Grid<T>, ParseError>
+where
+    F: FnMut(Token) -> Result<Vec<T>, ParseError>,
+{
+    let mut width = None;
+    let rows = lines.map(|line| {
+        let token = line.token(0, expected)?;
+        line.end(1)?;
+        let row = f(token)?;
+        match width {
+            Some(width) if width != row.len() => {
+                Err(token.error(&format!("{} {} wide", expected, width)))
+            }
+            _ => {
+                width = Some(row.len());
+                Ok(row)
+            }
+        }
+    });
+    Ok(Grid::from_rows(rows.collect::<Result<_, _>>()?))
+}
+
+/// Parses a map of single decimal digits, like a height map
+pub fn parse_digits(s: &str, expected: &str) -> Result<Grid<u8>, ParseError> {
+    parse_rows(s, expected, |row| row.digits())
+}
+
+/// Parses a map of characters
+#[cfg(test)]
+pub fn parse_chars(s: &str, expected: &str) -> Result<Grid<char>, ParseError> {
+    parse_rows(s, expected, |row| Ok(row.text.chars().collect()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_grid() {
+        let grid = parse_digits("123\n456", "a row").unwrap();
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!(grid[(2, 0)], 3);
+        assert_eq!(grid.get((0, 2)), None);
+        assert_eq!(grid.row(1), &[4, 5, 6]);
+        assert_eq!(grid.column(1).collect::<Vec<_>>(), vec![&2, &5]);
+        assert_eq!(grid.columns().count(), 3);
+        assert_eq!(grid.transpose().to_string(), "14\n25\n36\n");
+        assert_eq!(grid.map(|d| d * 2).to_string(), "246\n81012\n");
+        assert_eq!(grid.iter().nth(4), Some(((1, 1), &5)));
+
+        assert_eq!(grid.neighbors4((0, 0)).collect::<Vec<_>>(), vec![(1, 0), (0, 1)]);
+        assert_eq!(grid.neighbors4((1, 1)).count(), 3);
+        assert_eq!(
+            grid.neighbors8((1, 0)).collect::<Vec<_>>(),
+            vec![(0, 0), (2, 0), (0, 1), (1, 1), (2, 1)]
+        );
+        assert_eq!(neighbors8((1, 1), 3, 3).count(), 8);
+
+        let grid = parse_chars("#.\n.#", "a row").unwrap();
+        assert_eq!(grid, Grid::from_fn(2, 2, |(x, y)| if x == y { '#' } else { '.' }));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let err = parse_digits("123\n 4567\n", "a row of heights").unwrap_err();
+        assert_eq!((err.line, err.column), (2, 2));
+        assert_eq!(
+            err.to_string(),
+            "line 2, column 2: expected a row of heights 3 wide, found '4567'"
+        );
+
+        let err = parse_digits("123\n4x6", "a row of heights").unwrap_err();
+        assert_eq!((err.column, err.token.as_str()), (2, "x"));
+    }
+}
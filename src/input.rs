@@ -0,0 +1,76 @@
+use std::borrow::Cow;
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+pub const INPUTS_DIR: &str = "inputs";
+
+/// Where a day's puzzle input should be read from
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Source {
+    /// `inputs/dayNN.txt`, falling back to the day's embedded fixture if it has one
+    Default,
+    File(PathBuf),
+    Stdin,
+}
+
+impl Source {
+    pub fn from_arg(arg: &str) -> Self {
+        if arg == "-" {
+            Source::Stdin
+        } else {
+            Source::File(PathBuf::from(arg))
+        }
+    }
+}
+
+pub fn default_path(day: u32) -> PathBuf {
+    Path::new(INPUTS_DIR).join(format!("day{:02}.txt", day))
+}
+
+/// Loads the puzzle input text for a day
+pub fn load(day: u32, source: &Source, fixture: &'static str) -> io::Result<Cow<'static, str>> {
+    match source {
+        Source::Default => {
+            let path = default_path(day);
+            if path.is_file() {
+                read_file(&path).map(Cow::from)
+            } else if !fixture.is_empty() {
+                Ok(Cow::from(fixture))
+            } else {
+                Err(io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("{} not found, and there's no embedded input", path.display()),
+                ))
+            }
+        }
+        Source::File(path) => read_file(path).map(Cow::from),
+        Source::Stdin => {
+            let mut s = String::new();
+            io::stdin().read_to_string(&mut s)?;
+            Ok(Cow::from(s))
+        }
+    }
+}
+
+fn read_file(path: &Path) -> io::Result<String> {
+    fs::read_to_string(path)
+        .map_err(|err| io::Error::new(err.kind(), format!("{}: {}", path.display(), err)))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_load() {
+        assert_eq!(default_path(3), Path::new("inputs").join("day03.txt"));
+        assert_eq!(Source::from_arg("-"), Source::Stdin);
+
+        assert_eq!(load(99, &Source::Default, "1\n2\n").unwrap(), "1\n2\n");
+        let err = load(99, &Source::Default, "").unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+
+        assert!(load(1, &Source::from_arg("does/not/exist.txt"), "").is_err());
+    }
+}
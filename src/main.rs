@@ -1,34 +1,87 @@
+mod bits;
+mod cli;
+mod geometry;
+mod grid;
+mod input;
 mod parsing;
-mod day01;
-mod day02;
-mod day03;
-mod day04;
-mod day05;
-mod day06;
-mod day07;
-mod day08;
-mod day09;
-mod day10;
-mod day11;
-mod day12;
-mod day13;
-mod day14;
-mod day15;
+mod registry;
+mod solution;
+
+use std::io;
+use std::process;
+
+registry::days! {
+    1 => day01::Day01,
+    2 => day02::Day02,
+    3 => day03::Day03,
+    4 => day04::Day04,
+    5 => day05::Day05,
+    6 => day06::Day06,
+    7 => day07::Day07,
+    8 => day08::Day08,
+    9 => day09::Day09,
+    10 => day10::Day10,
+    11 => day11::Day11,
+    12 => day12::Day12,
+    13 => day13::Day13,
+    14 => day14::Day14,
+    15 => day15::Day15,
+    16 => day16::Day16,
+    17 => day17::Day17,
+    18 => day18::Day18,
+    19 => day19::Day19,
+    20 => day20::Day20,
+    21 => day21::Day21,
+    22 => day22::Day22,
+    23 => day23::Day23,
+    24 => day24::Day24,
+    25 => day25::Day25,
+}
 
 fn main() {
-    day01::run();
-    day02::run();
-    day03::run();
-    day04::run();
-    day05::run();
-    day06::run();
-    day07::run();
-    day08::run();
-    day09::run();
-    day10::run();
-    day11::run();
-    day12::run();
-    day13::run();
-    day14::run();
-    day15::run();
+    match cli::parse_args(std::env::args().skip(1)) {
+        Ok(cli::Command::Run { days, parts, input }) => {
+            for day in days.iter().filter_map(|day| registry::find(*day)) {
+                let text = match input::load(day.day, &input, day.fixture) {
+                    Ok(text) => text,
+                    // days without an input are skipped unless the input was asked for, but
+                    // days which aren't implemented yet can report that without one
+                    Err(err)
+                        if err.kind() == io::ErrorKind::NotFound
+                            && input == input::Source::Default =>
+                    {
+                        if day.implemented {
+                            eprintln!("skipping day {}: {}", day.day, err);
+                            continue;
+                        }
+                        "".into()
+                    }
+                    Err(err) => {
+                        eprintln!("error: failed to load input for day {}: {}", day.day, err);
+                        process::exit(1);
+                    }
+                };
+                let answers = match (day.solve)(&text, parts) {
+                    Ok(answers) => answers,
+                    Err(err) => {
+                        eprintln!("error: failed to parse input for day {}: {}", day.day, err);
+                        process::exit(1);
+                    }
+                };
+                for (part, answer) in answers {
+                    let answer = answer.trim_end();
+                    if answer.contains('\n') {
+                        println!("Day {:>2}, part {}:\n{}", day.day, part, answer);
+                    } else {
+                        println!("Day {:>2}, part {}: {}", day.day, part, answer);
+                    }
+                }
+            }
+        }
+        Ok(cli::Command::Help) => println!("{}", cli::USAGE),
+        Err(err) => {
+            eprintln!("error: {}\n\n{}", err, cli::USAGE);
+            process::exit(2);
+        }
+    }
 }
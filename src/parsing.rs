@@ -1,21 +1,266 @@
-use std::borrow::Cow;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
 
-pub fn parse_input<T, F>(s: &str, mut f: F) -> Vec<T>
+/// Where and why the input failed to parse; line and column are 1-based
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub token: String,
+    pub expected: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}, column {}: expected {}, ", self.line, self.column, self.expected)?;
+        if self.token.is_empty() {
+            write!(f, "found end of line")
+        } else {
+            write!(f, "found '{}'", self.token)
+        }
+    }
+}
+
+impl Error for ParseError {}
+
+/// A piece of an input line along with where it was found
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Token<'a> {
+    pub text: &'a str,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl<'a> Token<'a> {
+    pub fn error(&self, expected: &str) -> ParseError {
+        ParseError {
+            line: self.line,
+            column: self.column,
+            token: self.text.to_string(),
+            expected: expected.to_string(),
+        }
+    }
+
+    pub fn parse<T: FromStr>(&self, expected: &str) -> Result<T, ParseError> {
+        self.text.parse::<T>().map_err(|_| self.error(expected))
+    }
+
+    /// Splits the token on a separator, keeping track of each piece's column
+    pub fn split(&self, sep: &str) -> Vec<Token<'a>> {
+        let mut column = self.column;
+        self.text
+            .split(sep)
+            .map(|text| {
+                let token = Token {
+                    text,
+                    line: self.line,
+                    column,
+                };
+                column += text.len() + sep.len();
+                token
+            })
+            .collect()
+    }
+
+    /// Splits the token on a separator which must produce exactly `n` pieces
+    pub fn split_n(&self, sep: &str, n: usize, expected: &str) -> Result<Vec<Token<'a>>, ParseError> {
+        let pieces = self.split(sep);
+        if pieces.len() == n {
+            Ok(pieces)
+        } else {
+            Err(self.error(expected))
+        }
+    }
+
+    /// Parses an inclusive range like `x=10..12`, where `axis` is the prefix before the bounds
+    pub fn range<T: FromStr + PartialOrd>(&self, axis: &str) -> Result<(T, T), ParseError> {
+        let bounds = match self.text.strip_prefix(axis) {
+            Some(bounds) => Token {
+                text: bounds,
+                column: self.column + axis.len(),
+                ..*self
+            },
+            None => return Err(self.error(&format!("a range like '{}min..max'", axis))),
+        };
+        let pieces = bounds.split_n("..", 2, "a range like 'min..max'")?;
+        let (min, max) = (pieces[0].parse("a number")?, pieces[1].parse("a number")?);
+        if min > max {
+            return Err(bounds.error("a range from the lower bound to the upper"));
+        }
+        Ok((min, max))
+    }
+
+    /// Each character of the token as its own token
+    pub fn chars(&self) -> impl Iterator<Item = Token<'a>> {
+        let token = *self;
+        token.text.char_indices().map(move |(i, c)| Token {
+            text: &token.text[i..i + c.len_utf8()],
+            line: token.line,
+            column: token.column + i,
+        })
+    }
+
+    /// Parses a token made up of single decimal digits, like a row of a height map
+    pub fn digits(&self) -> Result<Vec<u8>, ParseError> {
+        self.chars()
+            .map(|c| c.parse::<u8>("a digit"))
+            .collect()
+    }
+}
+
+/// A non-empty input line split into whitespace separated tokens
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Line<'a> {
+    pub number: usize,
+    pub text: &'a str,
+    pub tokens: Vec<Token<'a>>,
+}
+
+impl<'a> Line<'a> {
+    fn new(number: usize, raw: &'a str) -> Self {
+        let mut tokens = Vec::new();
+        let mut start = None;
+        let mut push = |from: usize, to: usize| {
+            tokens.push(Token {
+                text: &raw[from..to],
+                line: number,
+                column: from + 1,
+            })
+        };
+        for (i, c) in raw.char_indices() {
+            match (c.is_whitespace(), start) {
+                (true, Some(from)) => {
+                    push(from, i);
+                    start = None;
+                }
+                (false, None) => start = Some(i),
+                _ => {}
+            }
+        }
+        if let Some(from) = start {
+            push(from, raw.len());
+        }
+        Self {
+            number,
+            text: raw.trim(),
+            tokens,
+        }
+    }
+
+    /// The token at `index`, or an error pointing at the end of the line if there isn't one
+    pub fn token(&self, index: usize, expected: &str) -> Result<Token<'a>, ParseError> {
+        self.tokens.get(index).copied().ok_or_else(|| ParseError {
+            line: self.number,
+            column: self.tokens.last().map_or(1, |t| t.column + t.text.len()),
+            token: String::new(),
+            expected: expected.to_string(),
+        })
+    }
+
+    pub fn parse<T: FromStr>(&self, index: usize, expected: &str) -> Result<T, ParseError> {
+        self.token(index, expected)?.parse(expected)
+    }
+
+    /// Checks there's nothing after the first `count` tokens, pointing at the first extra one
+    pub fn end(&self, count: usize) -> Result<(), ParseError> {
+        match self.tokens.get(count) {
+            Some(extra) => Err(extra.error("the end of the line")),
+            None => Ok(()),
+        }
+    }
+
+    /// An error covering the whole line
+    pub fn error(&self, expected: &str) -> ParseError {
+        ParseError {
+            line: self.number,
+            column: self.tokens.first().map_or(1, |t| t.column),
+            token: self.text.to_string(),
+            expected: expected.to_string(),
+        }
+    }
+}
+
+/// Iterates over the non-empty lines of the input
+pub fn lines(s: &str) -> impl Iterator<Item = Line<'_>> {
+    s.split('\n')
+        .enumerate()
+        .map(|(i, raw)| Line::new(i + 1, raw))
+        .filter(|line| !line.text.is_empty())
+}
+
+/// An error for input which ran out before `expected` was found, pointing just past its last
+/// non-empty line
+pub fn end_of_input(s: &str, expected: &str) -> ParseError {
+    ParseError {
+        line: lines(s).last().map_or(1, |line| line.number + 1),
+        column: 1,
+        token: String::new(),
+        expected: expected.to_string(),
+    }
+}
+
+pub fn parse_input<T, F>(s: &str, f: F) -> Result<Vec<T>, ParseError>
 where
-    F: FnMut(Vec<&str>) -> T,
+    F: FnMut(Line) -> Result<T, ParseError>,
 {
-    s.split("\n")
-        .map(|line| line.trim())
-        .filter(|line| !line.is_empty())
-        .map(|line| line.split_whitespace().collect())
-        .map(|parts| f(parts))
-        .collect()
-}
-
-pub fn parse_lines(s: &str) -> Vec<Cow<str>> {
-    s.split("\n")
-        .map(|line| line.trim())
-        .filter(|line| !line.is_empty())
-        .map(|s| Cow::from(s))
-        .collect()
+    lines(s).map(f).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_tokens() {
+        let line = lines("\n\n  forward 5\n").next().unwrap();
+        assert_eq!(line.number, 3);
+        assert_eq!(line.text, "forward 5");
+        assert_eq!(line.token(1, "a distance").unwrap().column, 11);
+        assert_eq!(line.parse::<u32>(1, "a distance"), Ok(5));
+        assert_eq!(line.end(2), Ok(()));
+        let err = line.end(1).unwrap_err();
+        assert_eq!((err.column, err.token.as_str()), (11, "5"));
+
+        let pt = lines("  12,x -> 3,4").next().unwrap().tokens[0];
+        let pieces = pt.split(",");
+        assert_eq!(pieces[1].text, "x");
+        assert_eq!(pieces[1].column, 6);
+        assert!(pt.split_n(",", 3, "three coordinates").is_err());
+
+        let range = lines("x=-3..12").next().unwrap().tokens[0];
+        assert_eq!(range.range::<i32>("x="), Ok((-3, 12)));
+        assert_eq!(range.range::<i32>("y=").unwrap_err().column, 1);
+        let err = lines("x=4..3").next().unwrap().tokens[0].range::<i32>("x=").unwrap_err();
+        assert_eq!((err.column, err.token.as_str()), (3, "4..3"));
+
+        let digits = lines("1203").next().unwrap().tokens[0];
+        assert_eq!(digits.digits(), Ok(vec![1, 2, 0, 3]));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let err = parse_input("1\n2\n  3x\n", |line| line.parse::<u32>(0, "a number")).unwrap_err();
+        assert_eq!(
+            err,
+            ParseError {
+                line: 3,
+                column: 3,
+                token: "3x".to_string(),
+                expected: "a number".to_string(),
+            }
+        );
+        assert_eq!(err.to_string(), "line 3, column 3: expected a number, found '3x'");
+
+        let err = parse_input("up", |line| line.parse::<u32>(1, "a distance")).unwrap_err();
+        assert_eq!(err.column, 3);
+        assert_eq!(err.to_string(), "line 1, column 3: expected a distance, found end of line");
+
+        let err = lines("91a").next().unwrap().tokens[0].digits().unwrap_err();
+        assert_eq!((err.column, err.token.as_str()), (3, "a"));
+
+        assert_eq!(end_of_input("", "a number").line, 1);
+        let err = end_of_input("1\n\n2\n\n", "a number");
+        assert_eq!(err.to_string(), "line 4, column 1: expected a number, found end of line");
+    }
 }
@@ -0,0 +1,142 @@
+use std::fmt;
+
+use crate::parsing::ParseError;
+use crate::solution::Solution;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Part {
+    One,
+    Two,
+}
+
+impl fmt::Display for Part {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Part::One => write!(f, "1"),
+            Part::Two => write!(f, "2"),
+        }
+    }
+}
+
+/// Which parts of a day's puzzle should be run
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Parts(Option<Part>);
+
+impl Parts {
+    pub fn all() -> Self {
+        Self(None)
+    }
+
+    pub fn only(part: Part) -> Self {
+        Self(Some(part))
+    }
+
+    pub fn has(&self, part: Part) -> bool {
+        match self.0 {
+            Some(only) => only == part,
+            None => true,
+        }
+    }
+}
+
+/// The answer to each part that was run
+pub type Answers = Vec<(Part, String)>;
+
+pub struct Day {
+    pub day: u32,
+    /// Parses the input and returns the answer to each requested part
+    pub solve: fn(&str, Parts) -> Result<Answers, ParseError>,
+    /// Embedded puzzle input used when no input file is available, or empty if there isn't one
+    pub fixture: &'static str,
+    /// False for days which haven't been solved yet, which don't need an input to report that
+    pub implemented: bool,
+}
+
+/// Declares each day's module and registers its `Solution` in `DAYS`
+macro_rules! days {
+    ($($day:literal => $module:ident::$solution:ident,)*) => {
+        $(mod $module;)*
+
+        pub static DAYS: &[registry::Day] = &[
+            $(registry::Day {
+                day: $day,
+                solve: registry::solve::<$module::$solution>,
+                fixture: $module::INPUT,
+                implemented: <$module::$solution as solution::Solution>::IMPLEMENTED,
+            },)*
+        ];
+    };
+}
+pub(crate) use days;
+
+pub fn solve<S: Solution>(input: &str, parts: Parts) -> Result<Answers, ParseError> {
+    let solution = S::parse(input)?;
+    let mut answers = Vec::new();
+    if parts.has(Part::One) {
+        answers.push((Part::One, solution.part1().to_string()));
+    }
+    if parts.has(Part::Two) {
+        answers.push((Part::Two, solution.part2().to_string()));
+    }
+    Ok(answers)
+}
+
+pub fn all() -> &'static [Day] {
+    crate::DAYS
+}
+
+pub fn find(day: u32) -> Option<&'static Day> {
+    all().iter().find(|d| d.day == day)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_days_registered() {
+        let days = all().iter().map(|d| d.day).collect::<Vec<u32>>();
+        assert_eq!(days, (1..=25).collect::<Vec<u32>>());
+        assert_eq!(find(7).map(|d| d.day), Some(7));
+        assert!(find(0).is_none());
+    }
+
+    #[test]
+    fn test_fixture_answers() {
+        let expected = [
+            (1, "1553", "1597"),
+            (2, "1636725", "1872757425"),
+            (3, "4001724", "587895"),
+            (4, "60368", "17435"),
+            (5, "7468", "22364"),
+            (6, "361169", "1634946868992"),
+            (7, "352331", "99266250"),
+            (8, "548", "1074888"),
+            (9, "486", "1059300"),
+            (10, "436497", "2377613374"),
+            (11, "1655", "337"),
+            (12, "3230", "83475"),
+            (13, "621", "HKUJGAJZ"),
+            (14, "2170", "2422444761283"),
+            (15, "487", "2821"),
+        ];
+        for (day, part1, part2) in expected {
+            let day = find(day).unwrap();
+            let answers = vec![(Part::One, part1.to_string()), (Part::Two, part2.to_string())];
+            assert_eq!((day.solve)(day.fixture, Parts::all()), Ok(answers), "day {}", day.day);
+        }
+    }
+
+    #[test]
+    fn test_unsolved_days() {
+        assert!(find(22).unwrap().implemented);
+        for day in (23..=25).map(|day| find(day).unwrap()) {
+            assert!(!day.implemented);
+            let answers = vec![
+                (Part::One, "not implemented".to_string()),
+                (Part::Two, "not implemented".to_string()),
+            ];
+            assert_eq!((day.solve)("", Parts::all()), Ok(answers), "day {}", day.day);
+        }
+    }
+}
@@ -0,0 +1,46 @@
+use std::fmt;
+
+use crate::parsing::ParseError;
+
+/// A day's puzzle: parses the input once and answers both parts from it
+pub trait Solution: Sized {
+    type Answer1: fmt::Display;
+    type Answer2: fmt::Display;
+    /// False for days which haven't been solved yet, so they can run without an input
+    const IMPLEMENTED: bool = true;
+
+    fn parse(input: &str) -> Result<Self, ParseError>;
+    fn part1(&self) -> Self::Answer1;
+    fn part2(&self) -> Self::Answer2;
+}
+
+/// Answer for a part which hasn't been solved yet
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Unsolved;
+
+impl fmt::Display for Unsolved {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "not implemented")
+    }
+}
+
+/// Stand-in for a day which hasn't been solved yet: ignores its input and answers `Unsolved`
+pub struct Skeleton;
+
+impl Solution for Skeleton {
+    type Answer1 = Unsolved;
+    type Answer2 = Unsolved;
+    const IMPLEMENTED: bool = false;
+
+    fn parse(_input: &str) -> Result<Self, ParseError> {
+        Ok(Skeleton)
+    }
+
+    fn part1(&self) -> Unsolved {
+        Unsolved
+    }
+
+    fn part2(&self) -> Unsolved {
+        Unsolved
+    }
+}